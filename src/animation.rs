@@ -0,0 +1,134 @@
+use ggez::graphics::{Color, DrawMode, MeshBuilder};
+use std::collections::HashMap;
+use std::f32::consts::FRAC_PI_2;
+use std::time::Instant;
+
+use crate::chunk::ResourceKind;
+use crate::robot::RobotId;
+use crate::Map;
+use crate::{ANIMATION_DURATION, CELL_SIZE, ENERGY_COLOR, MINERALS_COLOR};
+
+// Effet ponctuel : un anneau qui s'élargit et s'estompe sur la case
+struct Effect {
+    cell: (usize, usize),
+    color: Color,
+    started: Instant,
+}
+
+// État propre à l'affichage, déduit de la simulation sans jamais la modifier :
+// positions avant le dernier tick, orientation des robots et effets en cours
+#[derive(Default)]
+pub struct Animator {
+    previous: HashMap<RobotId, (usize, usize)>,
+    headings: HashMap<RobotId, f32>,
+    // Cargaison de chaque extracteur avant le dernier tick
+    cargo: Vec<Option<ResourceKind>>,
+    effects: Vec<Effect>,
+}
+
+fn resource_color(kind: ResourceKind) -> Color {
+    match kind {
+        ResourceKind::Energy => ENERGY_COLOR,
+        ResourceKind::Minerals => MINERALS_COLOR,
+    }
+}
+
+impl Animator {
+    pub fn before_tick(&mut self, map: &Map) {
+        for id in map.robot_ids() {
+            if let Some(position) = map.robot_position(id) {
+                self.previous.insert(id, position);
+            }
+        }
+        self.cargo = map
+            .robot_extractors
+            .iter()
+            .map(|robot| robot.cargo)
+            .collect();
+    }
+
+    pub fn after_tick(&mut self, map: &Map) {
+        for id in map.robot_ids() {
+            if let (Some(&from), Some(to)) = (self.previous.get(&id), map.robot_position(id)) {
+                if let Some([dx, dy]) = step_vector(map, from, to) {
+                    self.headings.insert(id, dy.atan2(dx));
+                }
+            }
+        }
+
+        // Extraction et déchargement se voient au changement de cargaison
+        for (extractor, &before) in map.robot_extractors.iter().zip(&self.cargo) {
+            let kind = match (before, extractor.cargo) {
+                (None, Some(kind)) | (Some(kind), None) => Some(kind),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                self.effects.push(Effect {
+                    cell: (extractor.x, extractor.y),
+                    color: resource_color(kind),
+                    started: Instant::now(),
+                });
+            }
+        }
+    }
+
+    // Centre et rotation d'un robot, interpolés entre ses deux dernières
+    // positions selon la fraction `alpha` du tick écoulée
+    pub fn robot_pose(&self, map: &Map, id: RobotId, alpha: f32) -> Option<([f32; 2], f32)> {
+        let to = map.robot_position(id)?;
+        let center = map.topology.cell_center(to.0, to.1, CELL_SIZE);
+        let center = match self.previous.get(&id) {
+            Some(&from) => match step_vector(map, from, to) {
+                Some([dx, dy]) => [
+                    center[0] - dx * (1.0 - alpha),
+                    center[1] - dy * (1.0 - alpha),
+                ],
+                None => center,
+            },
+            None => center,
+        };
+        // La texture du robot regarde vers le haut
+        let rotation = self
+            .headings
+            .get(&id)
+            .map_or(0.0, |heading| heading + FRAC_PI_2);
+        Some((center, rotation))
+    }
+
+    // Ajoute les effets en cours au maillage ; renvoie false s'il est resté vide
+    pub fn build_effects(&mut self, map: &Map, mesh: &mut MeshBuilder) -> bool {
+        self.effects
+            .retain(|effect| effect.started.elapsed() < ANIMATION_DURATION);
+
+        for effect in &self.effects {
+            let progress =
+                effect.started.elapsed().as_secs_f32() / ANIMATION_DURATION.as_secs_f32();
+            let center = map
+                .topology
+                .cell_center(effect.cell.0, effect.cell.1, CELL_SIZE);
+            let mut color = effect.color;
+            color.a = 1.0 - progress;
+            mesh.circle(
+                DrawMode::stroke(2.0),
+                center,
+                CELL_SIZE * (0.3 + 0.5 * progress),
+                1.0,
+                color,
+            );
+        }
+
+        !self.effects.is_empty()
+    }
+}
+
+// Déplacement à l'écran entre deux cases voisines ; None pour un saut plus long,
+// par exemple en traversant le bord d'un tore, qui n'est pas interpolé
+fn step_vector(map: &Map, from: (usize, usize), to: (usize, usize)) -> Option<[f32; 2]> {
+    if from == to {
+        return None;
+    }
+    let start = map.topology.cell_center(from.0, from.1, CELL_SIZE);
+    let end = map.topology.cell_center(to.0, to.1, CELL_SIZE);
+    let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+    (dx.abs() <= CELL_SIZE * 1.5 && dy.abs() <= CELL_SIZE * 1.5).then_some([dx, dy])
+}
@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+use crate::chunk::ResourceKind;
+use crate::events::SimEvent;
+use crate::Map;
+
+// Gisement repéré par un robot : la case à exploiter en premier et le nombre
+// de cases voisines du même type dont les robots se souviennent
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deposit {
+    pub kind: ResourceKind,
+    pub x: usize,
+    pub y: usize,
+    pub size: usize,
+    // Déplacements depuis la station, sans tenir compte des obstacles
+    pub distance: usize,
+}
+
+impl Deposit {
+    // Plus d'unités estimées par déplacement depuis la station
+    fn outranks(&self, other: &Deposit) -> bool {
+        self.size * (other.distance + 1) > other.size * (self.distance + 1)
+    }
+}
+
+// Travaux d'extraction en attente à la station, du plus prioritaire au moins prioritaire
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Backlog {
+    pub jobs: Vec<Deposit>,
+}
+
+impl Backlog {
+    // À priorité égale, le gisement signalé le premier passe devant
+    pub fn push(&mut self, deposit: Deposit) {
+        let index = self
+            .jobs
+            .iter()
+            .position(|job| deposit.outranks(job))
+            .unwrap_or(self.jobs.len());
+        self.jobs.insert(index, deposit);
+    }
+
+    pub fn pop(&mut self) -> Option<Deposit> {
+        if self.jobs.is_empty() {
+            None
+        } else {
+            Some(self.jobs.remove(0))
+        }
+    }
+
+    // Unités que la station s'attend à recevoir
+    pub fn units(&self) -> usize {
+        self.jobs.iter().map(|job| job.size).sum()
+    }
+}
+
+impl Map {
+    // Cases du gisement qui contient (x, y), d'après la mémoire des robots
+    pub fn survey_deposit(
+        &self,
+        x: usize,
+        y: usize,
+        kind: ResourceKind,
+    ) -> HashSet<(usize, usize)> {
+        let remembered = |(x, y): (usize, usize)| {
+            self.observed(x, y).is_some_and(|memory| match kind {
+                ResourceKind::Energy => memory.energy,
+                ResourceKind::Minerals => memory.minerals,
+            })
+        };
+        if !remembered((x, y)) {
+            return HashSet::new();
+        }
+
+        let mut cells = HashSet::from([(x, y)]);
+        let mut queue = VecDeque::from([(x, y)]);
+        while let Some((x, y)) = queue.pop_front() {
+            for next in self.neighbors(x, y) {
+                if !cells.contains(&next) && remembered(next) {
+                    cells.insert(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        cells
+    }
+
+    // Gisement estimé à partir de (x, y), None si aucun robot ne s'en souvient
+    pub fn deposit_at(&self, x: usize, y: usize, kind: ResourceKind) -> Option<Deposit> {
+        let station = self.station()?;
+        let size = self.survey_deposit(x, y, kind).len();
+        (size > 0).then(|| Deposit {
+            kind,
+            x,
+            y,
+            size,
+            distance: self.distance(station, (x, y)),
+        })
+    }
+
+    // Un des gisements connus de la station ou des extracteurs se trouve dans `cells`
+    pub fn deposit_known(&self, cells: &HashSet<(usize, usize)>) -> bool {
+        self.backlog
+            .jobs
+            .iter()
+            .any(|job| cells.contains(&(job.x, job.y)))
+            || self
+                .robot_extractors
+                .iter()
+                .filter_map(|extractor| extractor.target_position)
+                .any(|target| cells.contains(&target))
+    }
+
+    // Une fois une case exploitée ou disparue, le reste de son gisement
+    // retourne dans la liste des travaux, en plusieurs morceaux si la case
+    // les reliait
+    pub fn requeue_remaining(&mut self, x: usize, y: usize) {
+        for (next_x, next_y) in self.neighbors(x, y) {
+            for kind in [ResourceKind::Energy, ResourceKind::Minerals] {
+                if let Some(deposit) = self.deposit_at(next_x, next_y, kind) {
+                    let cells = self.survey_deposit(next_x, next_y, kind);
+                    if !self.deposit_known(&cells) {
+                        self.backlog.push(deposit);
+                    }
+                }
+            }
+        }
+    }
+
+    // Un extracteur est rentré à la station sans aucun travail à lui confier
+    pub fn extractor_idle(&self) -> bool {
+        self.backlog.jobs.is_empty()
+            && self
+                .robot_extractors
+                .iter()
+                .any(|extractor| extractor.waiting)
+    }
+
+    // Gisement signalé par l'explorateur à son retour, réestimé avec ce que
+    // les robots en ont vu depuis sa découverte
+    pub fn report_deposit(&mut self, deposit: Deposit) {
+        let Some(deposit) = self.deposit_at(deposit.x, deposit.y, deposit.kind) else {
+            self.emit(SimEvent::DepositVanished {
+                x: deposit.x,
+                y: deposit.y,
+            });
+            return;
+        };
+        let cells = self.survey_deposit(deposit.x, deposit.y, deposit.kind);
+        if !self.deposit_known(&cells) {
+            self.backlog.push(deposit);
+            self.emit(SimEvent::DepositReported {
+                kind: deposit.kind,
+                x: deposit.x,
+                y: deposit.y,
+                size: deposit.size,
+            });
+        }
+    }
+
+    // Travail suivant pour l'extracteur libre ; les gisements que les robots
+    // ont vus disparaître entre-temps sont abandonnés
+    pub fn next_job(&mut self) -> Option<Deposit> {
+        while let Some(job) = self.backlog.pop() {
+            match self.deposit_at(job.x, job.y, job.kind) {
+                Some(deposit) => return Some(deposit),
+                None => {
+                    self.emit(SimEvent::DepositVanished { x: job.x, y: job.y });
+                    self.requeue_remaining(job.x, job.y);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::station::StationStrategy;
+    use crate::testing;
+
+    fn deposit(x: usize, size: usize, distance: usize) -> Deposit {
+        Deposit {
+            kind: ResourceKind::Minerals,
+            x,
+            y: 0,
+            size,
+            distance,
+        }
+    }
+
+    fn order(backlog: &Backlog) -> Vec<usize> {
+        backlog.jobs.iter().map(|job| job.x).collect()
+    }
+
+    #[test]
+    fn jobs_are_ordered_by_units_per_move() {
+        let mut backlog = Backlog::default();
+        backlog.push(deposit(1, 2, 9));
+        backlog.push(deposit(2, 4, 3));
+        backlog.push(deposit(3, 2, 0));
+        backlog.push(deposit(4, 6, 20));
+        assert_eq!(order(&backlog), vec![3, 2, 4, 1]);
+        assert_eq!(backlog.units(), 14);
+
+        assert_eq!(backlog.pop().map(|job| job.x), Some(3));
+        assert_eq!(order(&backlog), vec![2, 4, 1]);
+    }
+
+    #[test]
+    fn ties_keep_the_report_order() {
+        let mut backlog = Backlog::default();
+        backlog.push(deposit(1, 2, 3));
+        backlog.push(deposit(2, 4, 7));
+        backlog.push(deposit(3, 1, 1));
+        backlog.push(deposit(4, 5, 9));
+        assert_eq!(order(&backlog), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn extraction_splits_a_deposit_in_two() {
+        let mut map = testing::mission(5, 32, StationStrategy::ResourceCentroid);
+        for (x, y) in map.generated_cells() {
+            let cell = map.cell_mut(x, y);
+            cell.energy = false;
+            cell.minerals = false;
+            cell.observe();
+        }
+        // Trois cases de minerais en ligne, loin de la station
+        let (station_x, station_y) = map.station().unwrap();
+        let y = if station_y < 16 { 28 } else { 3 };
+        let x = if station_x < 16 { 28 } else { 3 };
+        for cell_x in x - 1..=x + 1 {
+            let cell = map.cell_mut(cell_x, y);
+            cell.obstacle = false;
+            cell.minerals = true;
+            cell.observe();
+        }
+        map.terrain_changed();
+        assert_eq!(map.survey_deposit(x, y, ResourceKind::Minerals).len(), 3);
+
+        let cell = map.cell_mut(x, y);
+        cell.minerals = false;
+        cell.observe();
+        map.terrain_changed();
+        map.requeue_remaining(x, y);
+
+        let mut parts: Vec<(usize, usize)> = map
+            .backlog
+            .jobs
+            .iter()
+            .map(|job| (job.x, job.size))
+            .collect();
+        parts.sort();
+        assert_eq!(parts, vec![(x - 1, 1), (x + 1, 1)]);
+
+        // Les morceaux déjà dans la liste des travaux n'y sont pas ajoutés deux fois
+        map.requeue_remaining(x, y);
+        assert_eq!(map.backlog.jobs.len(), 2);
+    }
+}
@@ -0,0 +1,377 @@
+use rayon::prelude::*;
+use std::io;
+use std::path::Path;
+
+use crate::chunk::Thresholds;
+use crate::metrics::Summary;
+use crate::robot::ExplorerStrategy;
+use crate::scenario::Fleet;
+use crate::station::{PlacementError, StationStrategy};
+use crate::topology::Topology;
+use crate::Map;
+
+// Grille de paramètres à explorer : chaque combinaison est jouée une fois par
+// graine, pour que les configurations soient comparées sur les mêmes mondes
+pub struct Sweep {
+    pub seeds: Vec<u32>,
+    pub ticks: u64,
+    pub sizes: Vec<usize>,
+    pub topologies: Vec<Topology>,
+    pub stations: Vec<StationStrategy>,
+    pub obstacle_thresholds: Vec<f64>,
+    pub energy_thresholds: Vec<f64>,
+    pub minerals_thresholds: Vec<f64>,
+    pub explorers: Vec<usize>,
+    pub extractors: Vec<usize>,
+    pub explorer_strategies: Vec<ExplorerStrategy>,
+}
+
+// Une combinaison de paramètres de la grille
+#[derive(Copy, Clone, Debug)]
+pub struct Configuration {
+    pub size: usize,
+    pub topology: Topology,
+    pub station: StationStrategy,
+    pub thresholds: Thresholds,
+    pub fleet: Fleet,
+    pub explorer_strategy: ExplorerStrategy,
+}
+
+// Moyenne et écart type d'une mesure sur les parties d'une configuration
+#[derive(Copy, Clone, Debug)]
+pub struct Statistic {
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Statistic {
+    // Sans valeur, la moyenne n'est pas définie (NaN)
+    fn new(values: &[f64]) -> Self {
+        let count = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = if values.len() > 1 {
+            values
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / (count - 1.0)
+        } else {
+            0.0
+        };
+        Statistic {
+            mean,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+// Mesures agrégées, dans l'ordre des colonnes du tableau
+const COLUMNS: [&str; 8] = [
+    "explored_fraction",
+    "deposits_discovered",
+    "units_delivered",
+    "ticks_to_completion",
+    "explorer_distance",
+    "extractor_distance",
+    "battery_used",
+    "idle_ticks",
+];
+
+fn columns(summary: &Summary) -> [Option<f64>; 8] {
+    [
+        Some(summary.explored_fraction),
+        Some(summary.deposits_discovered as f64),
+        Some(summary.units_delivered as f64),
+        // Seules les parties menées à leur terme comptent
+        summary.ticks_to_completion.map(|ticks| ticks as f64),
+        Some(summary.explorer.distance as f64),
+        Some(summary.extractor.distance as f64),
+        Some((summary.explorer.battery_used + summary.extractor.battery_used) as f64),
+        Some((summary.explorer.idle_ticks + summary.extractor.idle_ticks) as f64),
+    ]
+}
+
+pub struct Results {
+    pub configuration: Configuration,
+    pub runs: usize,
+    // Parties où la station n'a pas pu être placée
+    pub failures: usize,
+    // Parties menées à leur terme
+    pub completed: usize,
+    pub statistics: [Statistic; 8],
+}
+
+impl Sweep {
+    pub fn configurations(&self) -> Vec<Configuration> {
+        // Compositions de la flotte et stratégies, combinées à part pour
+        // limiter l'imbrication des boucles sur le monde
+        let mut robots = vec![];
+        for &explorers in &self.explorers {
+            for &extractors in &self.extractors {
+                for &explorer_strategy in &self.explorer_strategies {
+                    let fleet = Fleet {
+                        explorers,
+                        extractors,
+                    };
+                    robots.push((fleet, explorer_strategy));
+                }
+            }
+        }
+
+        let mut configurations = vec![];
+        for &size in &self.sizes {
+            for &topology in &self.topologies {
+                for &station in &self.stations {
+                    for &obstacle in &self.obstacle_thresholds {
+                        for &energy in &self.energy_thresholds {
+                            for &minerals in &self.minerals_thresholds {
+                                for &(fleet, explorer_strategy) in &robots {
+                                    configurations.push(Configuration {
+                                        size,
+                                        topology,
+                                        station,
+                                        thresholds: Thresholds {
+                                            obstacle,
+                                            energy,
+                                            minerals,
+                                        },
+                                        fleet,
+                                        explorer_strategy,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        configurations
+    }
+
+    // Toutes les parties sont réparties sur les cœurs du processeur
+    pub fn run(&self) -> Vec<Results> {
+        let configurations = self.configurations();
+        let missions: Vec<(usize, u32)> = (0..configurations.len())
+            .flat_map(|index| self.seeds.iter().map(move |&seed| (index, seed)))
+            .collect();
+        let summaries: Vec<(usize, Result<Summary, PlacementError>)> = missions
+            .into_par_iter()
+            .map(|(index, seed)| (index, mission(&configurations[index], seed, self.ticks)))
+            .collect();
+
+        configurations
+            .into_iter()
+            .enumerate()
+            .map(|(index, configuration)| {
+                let summaries: Vec<&Summary> = summaries
+                    .iter()
+                    .filter(|(mission, _)| *mission == index)
+                    .filter_map(|(_, summary)| summary.as_ref().ok())
+                    .collect();
+                let statistics = std::array::from_fn(|column| {
+                    let values: Vec<f64> = summaries
+                        .iter()
+                        .filter_map(|summary| columns(summary)[column])
+                        .collect();
+                    Statistic::new(&values)
+                });
+                Results {
+                    configuration,
+                    runs: self.seeds.len(),
+                    failures: self.seeds.len() - summaries.len(),
+                    completed: summaries
+                        .iter()
+                        .filter(|summary| summary.ticks_to_completion.is_some())
+                        .count(),
+                    statistics,
+                }
+            })
+            .collect()
+    }
+}
+
+// Une partie sans affichage, arrêtée à la fin de la mission ou après `ticks` ticks
+pub fn mission(
+    configuration: &Configuration,
+    seed: u32,
+    ticks: u64,
+) -> Result<Summary, PlacementError> {
+    let size = configuration.size;
+    let mut map = Map::new(seed, size, size, configuration.topology);
+    map.events.console = None;
+    map.generator.thresholds = configuration.thresholds;
+    map.generate_initial_area();
+    // La flotte est déployée avec la station
+    map.rules.fleet = configuration.fleet;
+    map.rules.explorer_strategy = configuration.explorer_strategy;
+    map.place_station(configuration.station)?;
+    map.rules.max_ticks = Some(ticks);
+    while !map.mission_over() {
+        map.update_robot();
+    }
+    Ok(map.summary())
+}
+
+fn configuration_fields(configuration: &Configuration) -> [String; 9] {
+    [
+        configuration.size.to_string(),
+        configuration.topology.to_string(),
+        configuration.station.to_string(),
+        configuration.thresholds.obstacle.to_string(),
+        configuration.thresholds.energy.to_string(),
+        configuration.thresholds.minerals.to_string(),
+        configuration.fleet.explorers.to_string(),
+        configuration.fleet.extractors.to_string(),
+        configuration.explorer_strategy.to_string(),
+    ]
+}
+
+const CONFIGURATION_COLUMNS: [&str; 9] = [
+    "size",
+    "topology",
+    "station",
+    "obstacle",
+    "energy",
+    "minerals",
+    "explorers",
+    "extractors",
+    "strategy",
+];
+
+// Tableau lisible : une ligne par configuration, « moyenne ± écart type » par mesure
+pub fn print_table(results: &[Results]) {
+    let mut header: Vec<String> = CONFIGURATION_COLUMNS.map(String::from).to_vec();
+    header.extend(["runs", "failed", "completed"].map(String::from));
+    header.extend(COLUMNS.map(String::from));
+
+    let mut rows = vec![header];
+    for result in results {
+        let mut row = configuration_fields(&result.configuration).to_vec();
+        row.push(result.runs.to_string());
+        row.push(result.failures.to_string());
+        row.push(result.completed.to_string());
+        row.extend(result.statistics.iter().map(|statistic| {
+            if statistic.mean.is_nan() {
+                "-".to_string()
+            } else {
+                format!("{:.2} ± {:.2}", statistic.mean, statistic.stddev)
+            }
+        }));
+        rows.push(row);
+    }
+
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        println!("{}", cells.join("  "));
+    }
+}
+
+// Même tableau au format CSV, avec une colonne pour la moyenne et une pour l'écart type
+pub fn write_csv(results: &[Results], path: &Path) -> io::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+
+    let mut header: Vec<String> = CONFIGURATION_COLUMNS.map(String::from).to_vec();
+    header.extend(["runs", "failed", "completed"].map(String::from));
+    for column in COLUMNS {
+        header.push(format!("{}_mean", column));
+        header.push(format!("{}_stddev", column));
+    }
+    writer.write_record(&header)?;
+
+    for result in results {
+        let mut row = configuration_fields(&result.configuration).to_vec();
+        row.push(result.runs.to_string());
+        row.push(result.failures.to_string());
+        row.push(result.completed.to_string());
+        for statistic in &result.statistics {
+            // Cellules vides pour une mesure jamais relevée
+            if statistic.mean.is_nan() {
+                row.extend([String::new(), String::new()]);
+            } else {
+                row.push(statistic.mean.to_string());
+                row.push(statistic.stddev.to_string());
+            }
+        }
+        writer.write_record(&row)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sweep() -> Sweep {
+        Sweep {
+            seeds: vec![7],
+            ticks: 20000,
+            sizes: vec![16, 32],
+            topologies: vec![Topology::Square8],
+            stations: vec![StationStrategy::ResourceCentroid],
+            obstacle_thresholds: vec![Thresholds::default().obstacle],
+            energy_thresholds: vec![Thresholds::default().energy],
+            minerals_thresholds: vec![Thresholds::default().minerals],
+            explorers: vec![1, 2],
+            extractors: vec![3],
+            explorer_strategies: vec![ExplorerStrategy::Random, ExplorerStrategy::Frontier],
+        }
+    }
+
+    // La flotte et la stratégie varient le plus vite, après les paramètres du monde
+    #[test]
+    fn configurations_cover_fleet_and_strategy() {
+        let configurations = sweep().configurations();
+        assert_eq!(configurations.len(), 8);
+        let robots: Vec<(usize, usize, ExplorerStrategy)> = configurations[..4]
+            .iter()
+            .map(|configuration| {
+                assert_eq!(configuration.size, 16);
+                let fleet = configuration.fleet;
+                (
+                    fleet.explorers,
+                    fleet.extractors,
+                    configuration.explorer_strategy,
+                )
+            })
+            .collect();
+        assert_eq!(
+            robots,
+            [
+                (1, 3, ExplorerStrategy::Random),
+                (1, 3, ExplorerStrategy::Frontier),
+                (2, 3, ExplorerStrategy::Random),
+                (2, 3, ExplorerStrategy::Frontier),
+            ]
+        );
+        assert_eq!(configurations[4].size, 32);
+    }
+
+    #[test]
+    fn mission_deploys_the_configured_fleet() {
+        let mut sweep = sweep();
+        sweep.sizes = vec![32];
+        sweep.explorers = vec![2];
+        sweep.explorer_strategies = vec![ExplorerStrategy::Frontier];
+        let results = sweep.run();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].failures, 0);
+        assert_eq!(results[0].completed, 1);
+        // Colonnes de la flotte et de la stratégie dans le tableau
+        assert_eq!(
+            configuration_fields(&results[0].configuration)[6..],
+            ["2", "3", "frontier"]
+        );
+    }
+}
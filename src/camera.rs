@@ -0,0 +1,83 @@
+use ggez::graphics::{DrawParam, Rect};
+use std::ops::Range;
+
+use crate::robot::RobotId;
+use crate::Map;
+use crate::{CELL_SIZE, MAX_ZOOM, MIN_ZOOM};
+
+// Caméra de la fenêtre : centre de la vue en pixels du monde et facteur de zoom.
+// La vue occupe la zone `view` de l'écran, entre les panneaux latéraux
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+    pub follow: Option<RobotId>,
+    pub dragging: bool,
+}
+
+impl Camera {
+    pub fn new(center: [f32; 2]) -> Self {
+        Camera {
+            x: center[0],
+            y: center[1],
+            zoom: 1.0,
+            follow: None,
+            dragging: false,
+        }
+    }
+
+    pub fn center_on(&mut self, center: [f32; 2]) {
+        self.x = center[0];
+        self.y = center[1];
+    }
+
+    // Déplacement exprimé en pixels de l'écran ; désactive le suivi
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x -= dx / self.zoom;
+        self.y -= dy / self.zoom;
+        self.follow = None;
+    }
+
+    // Zoom en gardant fixe le point du monde situé sous le curseur
+    pub fn zoom_at(&mut self, factor: f32, screen: [f32; 2], view: Rect) {
+        let before = self.to_world(screen, view);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let after = self.to_world(screen, view);
+        self.x += before[0] - after[0];
+        self.y += before[1] - after[1];
+    }
+
+    pub fn to_world(&self, screen: [f32; 2], view: Rect) -> [f32; 2] {
+        [
+            (screen[0] - view.x - view.w / 2.0) / self.zoom + self.x,
+            (screen[1] - view.y - view.h / 2.0) / self.zoom + self.y,
+        ]
+    }
+
+    // Transformation à appliquer pour dessiner en pixels du monde
+    pub fn transform(&self, view: Rect) -> DrawParam {
+        DrawParam::default()
+            .dest([
+                view.x + view.w / 2.0 - self.x * self.zoom,
+                view.y + view.h / 2.0 - self.y * self.zoom,
+            ])
+            .scale([self.zoom, self.zoom])
+    }
+
+    // Cases visibles à l'écran, avec une marge d'une case
+    pub fn visible_cells(&self, map: &Map, view: Rect) -> (Range<usize>, Range<usize>) {
+        let top_left = self.to_world([view.x, view.y], view);
+        let bottom_right = self.to_world([view.right(), view.bottom()], view);
+        let row_height = map.topology.row_height(CELL_SIZE);
+
+        let first = |value: f32, size: f32| (value / size).floor().max(1.0) as usize - 1;
+        let last = |value: f32, size: f32, count: usize| {
+            ((value / size).ceil().max(0.0) as usize + 1).min(count)
+        };
+
+        (
+            first(top_left[0], CELL_SIZE)..last(bottom_right[0], CELL_SIZE, map.width),
+            first(top_left[1], row_height)..last(bottom_right[1], row_height, map.height),
+        )
+    }
+}
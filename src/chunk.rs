@@ -0,0 +1,195 @@
+use noise::{Fbm, NoiseFn, Perlin};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::topology::Topology;
+use crate::{ELEVATION_SCALE, ENERGY_THRESHOLD, MINERALS_THRESHOLD, OBSTACLE_THRESHOLD};
+
+pub const CHUNK_SIZE: usize = 16;
+
+// Types de ressources exploitables
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    Energy,
+    Minerals,
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ResourceKind::Energy => "energy",
+            ResourceKind::Minerals => "minerals",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Dernier état observé d'une case par les capteurs des robots
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Memory {
+    pub obstacle: bool,
+    pub energy: bool,
+    pub minerals: bool,
+    pub science_interests: bool,
+}
+
+// Contenu d'une case de la carte
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub energy: bool,
+    pub minerals: bool,
+    pub science_interests: bool,
+    pub obstacle: bool,
+    pub elevation: f64,
+    // Ressource apparue pendant la mission, par repousse ou météore ;
+    // la collecte complète ne l'attend pas
+    pub renewed: bool,
+    pub explored: bool,
+    // None tant qu'aucun robot n'a vu la case
+    pub memory: Option<Memory>,
+}
+
+impl Cell {
+    pub fn has_resource(&self) -> bool {
+        self.energy || self.minerals
+    }
+
+    pub fn snapshot(&self) -> Memory {
+        Memory {
+            obstacle: self.obstacle,
+            energy: self.energy,
+            minerals: self.minerals,
+            science_interests: self.science_interests,
+        }
+    }
+
+    // Met à jour la mémoire de la case ; renvoie true à la première observation
+    pub fn observe(&mut self) -> bool {
+        let first = self.memory.is_none();
+        self.memory = Some(self.snapshot());
+        first
+    }
+
+    pub fn resource(&self) -> Option<ResourceKind> {
+        if self.energy {
+            Some(ResourceKind::Energy)
+        } else if self.minerals {
+            Some(ResourceKind::Minerals)
+        } else {
+            None
+        }
+    }
+}
+
+// Bloc de CHUNK_SIZE x CHUNK_SIZE cases généré d'un seul tenant
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    pub cells: Vec<Cell>,
+}
+
+impl Chunk {
+    pub fn cell(&self, local_x: usize, local_y: usize) -> &Cell {
+        &self.cells[local_y * CHUNK_SIZE + local_x]
+    }
+
+    pub fn cell_mut(&mut self, local_x: usize, local_y: usize) -> &mut Cell {
+        &mut self.cells[local_y * CHUNK_SIZE + local_x]
+    }
+}
+
+// Seuils de bruit au-dessus desquels une case devient un obstacle ou un gisement
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Thresholds {
+    pub obstacle: f64,
+    pub energy: f64,
+    pub minerals: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            obstacle: OBSTACLE_THRESHOLD,
+            energy: ENERGY_THRESHOLD,
+            minerals: MINERALS_THRESHOLD,
+        }
+    }
+}
+
+// Générateur déterministe : une case ne dépend que de la graine et de sa position
+pub struct Generator {
+    // À régler avant la génération des premiers chunks
+    pub thresholds: Thresholds,
+    topology: Topology,
+    width: usize,
+    height: usize,
+    fbm_energy: Fbm<Perlin>,
+    fbm_minerals: Fbm<Perlin>,
+    fbm_obstacles: Fbm<Perlin>,
+    fbm_elevation: Fbm<Perlin>,
+}
+
+impl Generator {
+    pub fn new(seed: u32, topology: Topology, width: usize, height: usize) -> Self {
+        Generator {
+            thresholds: Thresholds::default(),
+            topology,
+            width,
+            height,
+            fbm_energy: Fbm::<Perlin>::new(seed),
+            fbm_minerals: Fbm::<Perlin>::new(seed.wrapping_add(1)),
+            fbm_obstacles: Fbm::<Perlin>::new(seed.wrapping_add(2)),
+            fbm_elevation: Fbm::<Perlin>::new(seed.wrapping_add(3)),
+        }
+    }
+
+    pub fn generate(&self, chunk_x: usize, chunk_y: usize) -> Chunk {
+        let mut cells = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE);
+
+        for local_y in 0..CHUNK_SIZE {
+            for local_x in 0..CHUNK_SIZE {
+                let x = chunk_x * CHUNK_SIZE + local_x;
+                let y = chunk_y * CHUNK_SIZE + local_y;
+                cells.push(self.generate_cell(x, y));
+            }
+        }
+
+        Chunk { cells }
+    }
+
+    // Échantillonne le bruit selon la topologie de la grille
+    fn sample(&self, fbm: &Fbm<Perlin>, x: usize, y: usize, scale: f64) -> f64 {
+        let point = self
+            .topology
+            .noise_point(x, y, self.width, self.height, scale);
+        match self.topology {
+            Topology::Torus => fbm.get(point),
+            _ => fbm.get([point[0], point[1]]),
+        }
+    }
+
+    fn generate_cell(&self, x: usize, y: usize) -> Cell {
+        let energy = self.sample(&self.fbm_energy, x, y, 1.0) > self.thresholds.energy;
+        let minerals = self.sample(&self.fbm_minerals, x, y, 1.0) > self.thresholds.minerals;
+
+        // Les obstacles évitent les emplacements des ressources
+        let obstacle = !energy
+            && !minerals
+            && self.sample(&self.fbm_obstacles, x, y, 1.0) > self.thresholds.obstacle;
+
+        // Hauteur ramenée entre 0 et 1
+        let elevation_noise = self.sample(&self.fbm_elevation, x, y, ELEVATION_SCALE);
+        let elevation = ((elevation_noise + 1.0) / 2.0).clamp(0.0, 1.0);
+
+        Cell {
+            energy,
+            minerals,
+            science_interests: false,
+            obstacle,
+            elevation,
+            renewed: false,
+            explored: false,
+            memory: None,
+        }
+    }
+}
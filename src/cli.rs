@@ -0,0 +1,576 @@
+use clap::{Args, Parser, Subcommand};
+use ggez::GameError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::batch::{self, Configuration, Sweep};
+use crate::chunk::Thresholds;
+use crate::events::{JsonLinesSink, Verbosity};
+use crate::gui;
+use crate::map::check_map_size;
+use crate::replay::{Replay, ReplayError};
+use crate::robot::ExplorerStrategy;
+use crate::scenario::{check_fleet_size, check_threshold, Fleet, Scenario};
+use crate::station::StationStrategy;
+use crate::topology::Topology;
+use crate::tui;
+use crate::Map;
+use crate::{
+    BATCH_RUNS, BENCH_RUNS, BENCH_SIZE, BENCH_TICKS, EXPLORERS, EXTRACTORS, HEADLESS_TICKS,
+    MAP_SIZE, MAX_FULL_MAP_SIZE, MAX_MAP_SIZE,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "cosmobots",
+    version,
+    about = "Exploration and mining robots on a procedurally generated planet",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    // Sans sous-commande, les options de `run` s'appliquent directement
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(about = "Run the simulation in a window (default) or in the terminal")]
+    Run(RunArgs),
+    #[command(about = "Run a mission without display and print its summary")]
+    Headless(HeadlessArgs),
+    #[command(about = "Generate a whole world and write its terrain to a map file")]
+    GenMap(GenMapArgs),
+    #[command(about = "Play back a replay file recorded with --record")]
+    Replay(ReplayArgs),
+    #[command(about = "Measure simulation and world generation speed")]
+    Bench(BenchArgs),
+    #[command(about = "Run headless missions over a grid of parameters")]
+    Batch(BatchArgs),
+}
+
+// Monde de la mission : généré à partir d'une graine ou chargé depuis un fichier de carte
+#[derive(Args)]
+pub struct WorldArgs {
+    #[arg(long, help = "World seed, random by default")]
+    seed: Option<u32>,
+    #[arg(long, default_value_t = MAP_SIZE, value_parser = map_size, help = "Width and height of the world in cells")]
+    size: usize,
+    #[arg(long, default_value_t = Topology::Square8, help = "Grid topology: square4, square8, hex or torus")]
+    topology: Topology,
+    #[arg(long, default_value_t = StationStrategy::ResourceCentroid, help = "Station placement: random, centroid, reachable or x,y")]
+    station: StationStrategy,
+    #[arg(long, help = "Load the terrain from a file written by gen-map")]
+    map: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["size", "topology", "station", "map"],
+        help = "Load the world and mission rules from a TOML scenario file"
+    )]
+    config: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["seed", "size", "topology", "station", "map", "config"],
+        help = "Resume a mission from a snapshot saved with F6 or --snapshot"
+    )]
+    resume: Option<PathBuf>,
+}
+
+// Sorties de la mission : journal des événements et mesures
+#[derive(Args)]
+pub struct OutputArgs {
+    #[arg(
+        long,
+        help = "Detail of the console and HUD event log: quiet, normal or verbose"
+    )]
+    verbosity: Option<Verbosity>,
+    #[arg(long, help = "Write every event to a JSON Lines file")]
+    events: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Export mission metrics at the end (.json, otherwise CSV)"
+    )]
+    metrics: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    #[command(flatten)]
+    world: WorldArgs,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[arg(long, help = "Use the terminal interface instead of a window")]
+    tui: bool,
+    #[arg(
+        long,
+        conflicts_with = "map",
+        help = "Record the mission to a replay file"
+    )]
+    record: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct HeadlessArgs {
+    #[command(flatten)]
+    world: WorldArgs,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[arg(
+        long,
+        help = "Maximum number of ticks, 5000 unless the scenario sets a limit"
+    )]
+    ticks: Option<u64>,
+    #[arg(
+        long,
+        conflicts_with = "map",
+        help = "Record the mission to a replay file"
+    )]
+    record: Option<PathBuf>,
+    #[arg(long, help = "Save a snapshot of the mission when it stops")]
+    snapshot: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct GenMapArgs {
+    #[arg(help = "Map file to write")]
+    output: PathBuf,
+    #[arg(long, help = "World seed, random by default")]
+    seed: Option<u32>,
+    #[arg(long, default_value_t = MAP_SIZE, value_parser = full_map_size, help = "Width and height of the world in cells")]
+    size: usize,
+    #[arg(long, default_value_t = Topology::Square8, help = "Grid topology: square4, square8, hex or torus")]
+    topology: Topology,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    #[arg(help = "Replay file recorded with --record")]
+    file: PathBuf,
+    #[command(flatten)]
+    output: OutputArgs,
+    #[arg(long, help = "Use the terminal interface instead of a window")]
+    tui: bool,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[arg(long, default_value_t = BENCH_TICKS, help = "Ticks simulated per mission")]
+    ticks: u64,
+    #[arg(long, default_value_t = BENCH_SIZE, value_parser = full_map_size, help = "Width and height of the worlds in cells")]
+    size: usize,
+    #[arg(long, default_value_t = BENCH_RUNS, help = "Missions per topology")]
+    runs: u32,
+}
+
+#[derive(Args)]
+pub struct BatchArgs {
+    #[arg(long, default_value_t = BATCH_RUNS, help = "Missions per configuration, with seeds 0, 1, 2...")]
+    runs: u32,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        conflicts_with = "runs",
+        help = "Seeds played by every configuration, comma-separated, instead of --runs"
+    )]
+    seeds: Vec<u32>,
+    #[arg(long, default_value_t = HEADLESS_TICKS, help = "Maximum number of ticks per mission")]
+    ticks: u64,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = map_size,
+        help = "World sizes, comma-separated"
+    )]
+    sizes: Vec<usize>,
+    #[arg(long, value_delimiter = ',', help = "Grid topologies, comma-separated")]
+    topologies: Vec<Topology>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Station placement strategies, comma-separated"
+    )]
+    stations: Vec<StationStrategy>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = threshold,
+        help = "Obstacle thresholds, comma-separated"
+    )]
+    obstacle: Vec<f64>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = threshold,
+        help = "Energy thresholds, comma-separated"
+    )]
+    energy: Vec<f64>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = threshold,
+        help = "Minerals thresholds, comma-separated"
+    )]
+    minerals: Vec<f64>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = fleet_size,
+        help = "Numbers of explorers, comma-separated"
+    )]
+    explorers: Vec<usize>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_parser = fleet_size,
+        help = "Numbers of extractors, comma-separated"
+    )]
+    extractors: Vec<usize>,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Explorer strategies, comma-separated"
+    )]
+    explorer_strategies: Vec<ExplorerStrategy>,
+    #[arg(long, help = "Also write the results table as CSV")]
+    output: Option<PathBuf>,
+}
+
+// Taille de monde passée en argument ; clap refuse les tailles hors bornes avec le code 2
+fn map_size(value: &str) -> Result<usize, String> {
+    parse_size(value, MAX_MAP_SIZE)
+}
+
+// Taille d'un monde généré en entier, plus petite
+fn full_map_size(value: &str) -> Result<usize, String> {
+    parse_size(value, MAX_FULL_MAP_SIZE)
+}
+
+// Seuil de génération passé en argument, borné comme dans un scénario
+fn threshold(value: &str) -> Result<f64, String> {
+    let threshold: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    check_threshold(threshold).map_err(|message| format!("the threshold {}", message))?;
+    Ok(threshold)
+}
+
+// Nombre de robots d'un type passé en argument, borné comme dans un scénario
+fn fleet_size(value: &str) -> Result<usize, String> {
+    let count: usize = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number of robots", value))?;
+    check_fleet_size(count).map_err(|message| format!("the fleet size {}", message))?;
+    Ok(count)
+}
+
+fn parse_size(value: &str, max: usize) -> Result<usize, String> {
+    let size: usize = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number of cells", value))?;
+    check_map_size(size, max).map_err(|message| format!("the world size {}", message))?;
+    Ok(size)
+}
+
+// Erreurs remontées à l'utilisateur, chacune avec son code de sortie
+#[derive(Debug)]
+pub enum CliError {
+    // Paramètres incohérents : code 2, comme les erreurs d'arguments
+    Invalid(String),
+    // Fichier de carte ou de replay illisible : code 3
+    File(String),
+    // Entrées/sorties ou fenêtre : code 1
+    Io(io::Error),
+    Game(GameError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Invalid(message) | CliError::File(message) => write!(f, "{}", message),
+            CliError::Io(err) => write!(f, "{}", err),
+            CliError::Game(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Io(err)
+    }
+}
+
+impl From<GameError> for CliError {
+    fn from(err: GameError) -> Self {
+        CliError::Game(err)
+    }
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            CliError::Invalid(_) => ExitCode::from(2),
+            CliError::File(_) => ExitCode::from(3),
+            CliError::Io(_) | CliError::Game(_) => ExitCode::FAILURE,
+        }
+    }
+}
+
+pub fn run(cli: Cli) -> Result<(), CliError> {
+    match cli.command.unwrap_or(Command::Run(cli.run)) {
+        Command::Run(args) => run_window(args),
+        Command::Headless(args) => headless(args),
+        Command::GenMap(args) => gen_map(args),
+        Command::Replay(args) => replay(args),
+        Command::Bench(args) => bench(args),
+        Command::Batch(args) => run_batch(args),
+    }
+}
+
+// Monde prêt à démarrer : terrain généré, chargé ou décrit par un scénario, station placée
+fn build_world(world: &WorldArgs) -> Result<Map, CliError> {
+    let file_error = |path: &Path, err: &dyn fmt::Display| {
+        CliError::File(format!("{}: {}", path.display(), err))
+    };
+    // Une mission reprise a déjà sa station
+    if let Some(path) = &world.resume {
+        let map = Map::load_snapshot(path).map_err(|err| file_error(path, &err))?;
+        println!("Seed: {}, resuming at tick {}", map.seed, map.tick);
+        return Ok(map);
+    }
+    let (mut map, station) = match (&world.config, &world.map) {
+        (Some(path), _) => {
+            let scenario = Scenario::load(path).map_err(|err| file_error(path, &err))?;
+            (scenario.world(world.seed), scenario.station.placement)
+        }
+        (None, Some(path)) => (
+            Map::load_terrain(path, world.seed).map_err(|err| file_error(path, &err))?,
+            world.station,
+        ),
+        (None, None) => {
+            let seed = world.seed.unwrap_or_else(rand::random);
+            let mut map = Map::new(seed, world.size, world.size, world.topology);
+            // Génère le terrain autour du centre du monde
+            map.generate_initial_area();
+            (map, world.station)
+        }
+    };
+    println!("Seed: {}", map.seed);
+
+    map.place_station(station)
+        .map_err(|err| CliError::Invalid(err.to_string()))?;
+    Ok(map)
+}
+
+// Branche les sorties du journal demandées
+fn configure_output(map: &mut Map, output: &OutputArgs) -> Result<(), CliError> {
+    if let Some(verbosity) = output.verbosity {
+        map.events.console = Some(verbosity);
+        map.events.hud = verbosity;
+    }
+    if let Some(path) = &output.events {
+        map.events
+            .add_sink(Verbosity::Verbose, JsonLinesSink::create(path)?);
+    }
+    Ok(())
+}
+
+// Un enregistrement couvre toute la mission : une mission reprise ne peut
+// continuer que l'enregistrement contenu dans sa sauvegarde
+fn start_recording(map: &mut Map, record: Option<&Path>) -> Result<(), CliError> {
+    if record.is_none() || map.recording.is_some() {
+        return Ok(());
+    }
+    if map.tick > 0 {
+        return Err(CliError::Invalid(
+            "cannot record: the snapshot was saved without a recording".to_string(),
+        ));
+    }
+    map.recording = Some(Replay::new(map));
+    Ok(())
+}
+
+// Fichiers écrits en fin de partie : enregistrement et mesures de la mission
+fn finish(map: &mut Map, record: Option<&Path>, metrics: Option<&Path>) -> Result<(), CliError> {
+    if let (Some(recording), Some(path)) = (map.take_recording(), record) {
+        recording.save(path)?;
+        println!("Replay saved to {}", path.display());
+    }
+    if let Some(path) = metrics {
+        map.export_metrics(path)?;
+        println!("Metrics saved to {}", path.display());
+    }
+    Ok(())
+}
+
+fn run_window(args: RunArgs) -> Result<(), CliError> {
+    let mut map = build_world(&args.world)?;
+    start_recording(&mut map, args.record.as_deref())?;
+    configure_output(&mut map, &args.output)?;
+
+    if args.tui {
+        tui::run(&mut map, None)?;
+    } else {
+        map = gui::run(map, None)?;
+    }
+    finish(
+        &mut map,
+        args.record.as_deref(),
+        args.output.metrics.as_deref(),
+    )
+}
+
+fn headless(args: HeadlessArgs) -> Result<(), CliError> {
+    let mut map = build_world(&args.world)?;
+    // `--ticks` arrête la partie sans la terminer, pour qu'un instantané pris
+    // à ce moment puisse être repris plus loin
+    let ticks = args.ticks.or(map.rules.max_ticks).unwrap_or(HEADLESS_TICKS);
+    start_recording(&mut map, args.record.as_deref())?;
+    configure_output(&mut map, &args.output)?;
+
+    while !map.mission_over() && map.tick < ticks {
+        map.update_robot();
+    }
+
+    let summary = map.summary();
+    println!("Ticks: {}", summary.ticks);
+    println!("Explored: {:.1}%", summary.explored_fraction * 100.0);
+    println!("Deposits discovered: {}", summary.deposits_discovered);
+    println!("Units delivered: {}", summary.units_delivered);
+    // L'issue d'une mission terminée est déjà annoncée par l'événement MissionEnded
+    if summary.outcome.is_none() {
+        println!("Mission still running");
+    }
+    if let Some(path) = &args.snapshot {
+        map.save_snapshot(path)?;
+        println!("Snapshot saved to {}", path.display());
+    }
+    finish(
+        &mut map,
+        args.record.as_deref(),
+        args.output.metrics.as_deref(),
+    )
+}
+
+fn gen_map(args: GenMapArgs) -> Result<(), CliError> {
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut map = Map::new(seed, args.size, args.size, args.topology);
+    map.save_terrain(&args.output)?;
+    println!("Seed: {}", seed);
+    println!("Map saved to {}", args.output.display());
+    Ok(())
+}
+
+fn replay(args: ReplayArgs) -> Result<(), CliError> {
+    let replay = Replay::load(&args.file)
+        .map_err(|err| CliError::File(format!("{}: {}", args.file.display(), err)))?;
+    let replay = Arc::new(replay);
+    let mut map = replay
+        .start()
+        .map_err(|err| CliError::File(format!("{}: {}", args.file.display(), err)))?;
+    configure_output(&mut map, &args.output)?;
+
+    if args.tui {
+        tui::run(&mut map, Some(replay))?;
+    } else {
+        map = gui::run(map, Some(replay))?;
+    }
+    finish(&mut map, None, args.output.metrics.as_deref())?;
+    match map.replay_diverged() {
+        Some(tick) => Err(CliError::File(format!(
+            "{}: {}",
+            args.file.display(),
+            ReplayError::Diverged(tick)
+        ))),
+        None => Ok(()),
+    }
+}
+
+// Vitesse de la simulation pour chaque topologie, puis vitesse de génération du terrain
+fn bench(args: BenchArgs) -> Result<(), CliError> {
+    let topologies = [
+        Topology::Square4,
+        Topology::Square8,
+        Topology::Hex,
+        Topology::Torus,
+    ];
+    for topology in topologies {
+        let configuration = Configuration {
+            size: args.size,
+            topology,
+            station: StationStrategy::ResourceCentroid,
+            thresholds: Thresholds::default(),
+            fleet: Fleet::default(),
+            explorer_strategy: ExplorerStrategy::default(),
+        };
+        let start = Instant::now();
+        let mut ticks = 0;
+        for seed in 0..args.runs {
+            let summary = batch::mission(&configuration, seed, args.ticks)
+                .map_err(|err| CliError::Invalid(err.to_string()))?;
+            ticks += summary.ticks;
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        println!(
+            "{:>8}: {} ticks in {:.2}s, {:.0} ticks/s",
+            topology.to_string(),
+            ticks,
+            elapsed,
+            ticks as f64 / elapsed
+        );
+    }
+
+    let start = Instant::now();
+    let mut map = Map::new(0, args.size, args.size, Topology::Square8);
+    map.generate_all();
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "generate: {} chunks in {:.3}s, {:.0} chunks/s",
+        map.chunks.len(),
+        elapsed,
+        map.chunks.len() as f64 / elapsed
+    );
+    Ok(())
+}
+
+fn run_batch(args: BatchArgs) -> Result<(), CliError> {
+    // Un axe non précisé garde sa valeur par défaut
+    fn or_default<T>(values: Vec<T>, default: T) -> Vec<T> {
+        if values.is_empty() {
+            vec![default]
+        } else {
+            values
+        }
+    }
+
+    let thresholds = Thresholds::default();
+    let sweep = Sweep {
+        seeds: if args.seeds.is_empty() {
+            (0..args.runs).collect()
+        } else {
+            args.seeds
+        },
+        ticks: args.ticks,
+        sizes: or_default(args.sizes, MAP_SIZE),
+        topologies: or_default(args.topologies, Topology::Square8),
+        stations: or_default(args.stations, StationStrategy::ResourceCentroid),
+        obstacle_thresholds: or_default(args.obstacle, thresholds.obstacle),
+        energy_thresholds: or_default(args.energy, thresholds.energy),
+        minerals_thresholds: or_default(args.minerals, thresholds.minerals),
+        explorers: or_default(args.explorers, EXPLORERS),
+        extractors: or_default(args.extractors, EXTRACTORS),
+        explorer_strategies: or_default(args.explorer_strategies, ExplorerStrategy::default()),
+    };
+    let results = sweep.run();
+    batch::print_table(&results);
+    if let Some(path) = args.output {
+        batch::write_csv(&results, &path)?;
+        println!("Results saved to {}", path.display());
+    }
+    Ok(())
+}
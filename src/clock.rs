@@ -0,0 +1,145 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::{MAX_SPEED_FRAME_BUDGET, MAX_TICKS_PER_FRAME, SPEEDS, TICK_DURATION};
+
+// Vitesse de la simulation : un multiple de la cadence de base, ou aussi vite que possible
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Speed {
+    Scaled(f64),
+    Max,
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Speed::Scaled(factor) => write!(f, "{}x", factor),
+            Speed::Max => write!(f, "max"),
+        }
+    }
+}
+
+// Pas de temps fixe de la simulation, indépendant de la cadence d'affichage :
+// le temps écoulé entre deux images s'accumule et est converti en ticks
+pub struct Clock {
+    pub paused: bool,
+    speed_index: usize,
+    accumulator: Duration,
+    step_requested: bool,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock {
+            paused: false,
+            speed_index: SPEEDS.iter().position(|&speed| speed == 1.0).unwrap_or(0),
+            accumulator: Duration::from_secs(0),
+            step_requested: false,
+        }
+    }
+
+    // Le dernier cran après SPEEDS correspond à la vitesse maximale
+    pub fn speed(&self) -> Speed {
+        match SPEEDS.get(self.speed_index) {
+            Some(&factor) => Speed::Scaled(factor),
+            None => Speed::Max,
+        }
+    }
+
+    pub fn faster(&mut self) {
+        self.speed_index = (self.speed_index + 1).min(SPEEDS.len());
+        self.accumulator = Duration::from_secs(0);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed_index = self.speed_index.saturating_sub(1);
+        self.accumulator = Duration::from_secs(0);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.accumulator = Duration::from_secs(0);
+    }
+
+    // Avance d'un seul tick ; met la simulation en pause
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step_requested = true;
+    }
+
+    // Fraction du tick suivant déjà écoulée, pour interpoler l'affichage
+    pub fn alpha(&self) -> f32 {
+        match self.speed() {
+            Speed::Scaled(factor) if !self.paused => {
+                let period = TICK_DURATION.div_f64(factor);
+                (self.accumulator.as_secs_f32() / period.as_secs_f32()).min(1.0)
+            }
+            _ => 1.0,
+        }
+    }
+
+    // Appelle `tick` autant de fois que le temps écoulé depuis la dernière image le demande ;
+    // `tick` renvoie false quand la simulation n'a plus rien à jouer, ce qui arrête la boucle
+    pub fn advance(&mut self, delta: Duration, mut tick: impl FnMut() -> bool) {
+        if self.paused {
+            if self.step_requested {
+                self.step_requested = false;
+                tick();
+            }
+            return;
+        }
+
+        match self.speed() {
+            Speed::Scaled(factor) => {
+                self.accumulator += delta;
+                let period = TICK_DURATION.div_f64(factor);
+                let mut ticks = 0;
+                let mut running = true;
+                while running && self.accumulator >= period && ticks < MAX_TICKS_PER_FRAME {
+                    self.accumulator -= period;
+                    ticks += 1;
+                    running = tick();
+                }
+                // Le retard qui ne peut pas être rattrapé est abandonné
+                if !running || ticks == MAX_TICKS_PER_FRAME {
+                    self.accumulator = Duration::from_secs(0);
+                }
+            }
+            Speed::Max => {
+                let start = Instant::now();
+                while start.elapsed() < MAX_SPEED_FRAME_BUDGET && tick() {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Nombre d'appels à `tick` quand la simulation s'arrête au bout de `limit` ticks
+    fn ticks_until_stopped(clock: &mut Clock, delta: Duration, limit: u32) -> u32 {
+        let mut calls = 0;
+        clock.advance(delta, || {
+            calls += 1;
+            calls < limit
+        });
+        calls
+    }
+
+    #[test]
+    fn max_speed_stops_when_the_simulation_does() {
+        let mut clock = Clock::new();
+        while clock.speed() != Speed::Max {
+            clock.faster();
+        }
+        assert_eq!(ticks_until_stopped(&mut clock, Duration::ZERO, 3), 3);
+    }
+
+    #[test]
+    fn scaled_speed_drops_the_backlog_once_stopped() {
+        let mut clock = Clock::new();
+        assert_eq!(ticks_until_stopped(&mut clock, TICK_DURATION * 10, 3), 3);
+        assert_eq!(clock.alpha(), 0.0);
+    }
+}
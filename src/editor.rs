@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::events::SimEvent;
+use crate::replay::Action;
+use crate::Map;
+
+// Ce que le pinceau du mode édition dépose sur une case
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Brush {
+    Obstacle,
+    Energy,
+    Minerals,
+    Erase,
+}
+
+impl fmt::Display for Brush {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Brush::Obstacle => "obstacle",
+            Brush::Energy => "energy",
+            Brush::Minerals => "minerals",
+            Brush::Erase => "erase",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Brush {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "obstacle" => Ok(Brush::Obstacle),
+            "energy" => Ok(Brush::Energy),
+            "minerals" => Ok(Brush::Minerals),
+            "erase" => Ok(Brush::Erase),
+            _ => Err(format!("unknown brush '{}'", s)),
+        }
+    }
+}
+
+impl Map {
+    // Modifie une case pendant la simulation ; les robots replanifient leur chemin
+    // à chaque tick et s'adaptent donc d'eux-mêmes. La station et les cases occupées
+    // par un robot ne peuvent pas être modifiées. Renvoie true si la case a changé.
+    pub fn paint(&mut self, x: usize, y: usize, brush: Brush) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        if self.occupied_cells().contains(&(x, y)) {
+            return false;
+        }
+
+        // Une modification sous les capteurs d'un robot est vue immédiatement
+        let visible = self.visible.contains(&(x, y));
+        let cell = self.cell_mut(x, y);
+        let before = *cell;
+        cell.renewed = false;
+        match brush {
+            Brush::Obstacle => {
+                cell.obstacle = true;
+                cell.energy = false;
+                cell.minerals = false;
+            }
+            Brush::Energy => {
+                cell.obstacle = false;
+                cell.energy = true;
+                cell.minerals = false;
+            }
+            Brush::Minerals => {
+                cell.obstacle = false;
+                cell.energy = false;
+                cell.minerals = true;
+            }
+            Brush::Erase => {
+                cell.obstacle = false;
+                cell.energy = false;
+                cell.minerals = false;
+            }
+        }
+
+        if visible {
+            cell.observe();
+        }
+        let changed = *cell != before;
+        if changed {
+            self.cell_changed(x, y, &before);
+            self.record(Action::Paint(x, y, brush));
+            self.emit(SimEvent::CellPainted { brush, x, y });
+        }
+        changed
+    }
+}
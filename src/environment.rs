@@ -0,0 +1,242 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+
+use crate::events::SimEvent;
+use crate::Map;
+use crate::{
+    METEOR_CHANCE, METEOR_MINERALS_CHANCE, REGROWTH_DELAY, STORM_CHANCE, STORM_DURATION,
+    STORM_RADIUS, STORM_SPEED,
+};
+
+// Gisement d'énergie épuisé qui se reconstituera plus tard
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Regrowth {
+    pub x: usize,
+    pub y: usize,
+    pub ready_at: u64,
+}
+
+// Tempête de poussière qui dérive sur la carte
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Storm {
+    pub x: f64,
+    pub y: f64,
+    pub dx: f64,
+    pub dy: f64,
+    pub radius: usize,
+    pub remaining: u32,
+}
+
+impl Storm {
+    // Sur un tore, un centre arrondi à la largeur de la carte désigne la colonne 0
+    pub fn center(&self) -> (usize, usize) {
+        (
+            self.x.round().max(0.0) as usize,
+            self.y.round().max(0.0) as usize,
+        )
+    }
+}
+
+// Événements de l'environnement, tirés d'un générateur dédié initialisé
+// avec la graine de la carte pour que deux parties identiques le restent
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Environment {
+    pub rng: Pcg32,
+    pub regrowth: Vec<Regrowth>,
+    pub storms: Vec<Storm>,
+}
+
+impl Environment {
+    pub fn new(seed: u32) -> Self {
+        Environment {
+            rng: Pcg32::seed_from_u64(seed as u64),
+            regrowth: vec![],
+            storms: vec![],
+        }
+    }
+}
+
+impl Map {
+    pub fn update_environment(&mut self) {
+        self.regrow_deposits();
+        self.move_storms();
+
+        if self.environment.rng.gen_bool(STORM_CHANCE) {
+            self.spawn_storm();
+        }
+        if self.environment.rng.gen_bool(METEOR_CHANCE) {
+            self.strike_meteor();
+        }
+    }
+
+    // Note l'épuisement d'un gisement d'énergie pour le faire repousser
+    pub fn schedule_regrowth(&mut self, x: usize, y: usize) {
+        self.environment.regrowth.push(Regrowth {
+            x,
+            y,
+            ready_at: self.tick + REGROWTH_DELAY,
+        });
+    }
+
+    fn regrow_deposits(&mut self) {
+        let tick = self.tick;
+        let (ready, pending) = std::mem::take(&mut self.environment.regrowth)
+            .into_iter()
+            .partition(|regrowth| regrowth.ready_at <= tick);
+        self.environment.regrowth = pending;
+
+        for Regrowth { x, y, .. } in ready {
+            let cell = self.cell_mut(x, y);
+            let before = *cell;
+            // Un météore a pu tomber entre-temps
+            if !cell.obstacle {
+                cell.energy = true;
+                cell.renewed = true;
+                self.cell_changed(x, y, &before);
+                self.emit(SimEvent::EnergyRegrown { x, y });
+            }
+        }
+    }
+
+    fn move_storms(&mut self) {
+        for storm in self.environment.storms.iter_mut() {
+            (storm.x, storm.y) = self.topology.wrap_position(
+                storm.x + storm.dx,
+                storm.y + storm.dy,
+                self.width,
+                self.height,
+            );
+            storm.remaining = storm.remaining.saturating_sub(1);
+        }
+        self.environment.storms.retain(|storm| storm.remaining > 0);
+    }
+
+    fn spawn_storm(&mut self) {
+        let cells = self.generated_cells();
+        let Some(&(x, y)) = cells.choose(&mut self.environment.rng) else {
+            return;
+        };
+        let angle = self.environment.rng.gen_range(0.0..std::f64::consts::TAU);
+
+        self.environment.storms.push(Storm {
+            x: x as f64,
+            y: y as f64,
+            dx: angle.cos() * STORM_SPEED,
+            dy: angle.sin() * STORM_SPEED,
+            radius: STORM_RADIUS,
+            remaining: STORM_DURATION,
+        });
+        self.emit(SimEvent::StormFormed { x, y });
+    }
+
+    fn strike_meteor(&mut self) {
+        // Le météore évite la station et les robots
+        let occupied = self.occupied_cells();
+        let cells: Vec<(usize, usize)> = self
+            .generated_cells()
+            .into_iter()
+            .filter(|cell| !occupied.contains(cell))
+            .collect();
+        let Some(&(x, y)) = cells.choose(&mut self.environment.rng) else {
+            return;
+        };
+
+        // Le cratère devient un obstacle entouré de nouveaux minerais
+        let crater = self.cell_mut(x, y);
+        crater.obstacle = true;
+        crater.energy = false;
+        crater.minerals = false;
+        crater.renewed = false;
+
+        for (nx, ny) in self.neighbors(x, y) {
+            if occupied.contains(&(nx, ny)) {
+                continue;
+            }
+            if self.environment.rng.gen_bool(METEOR_MINERALS_CHANCE) {
+                let cell = self.cell_mut(nx, ny);
+                if !cell.obstacle {
+                    cell.renewed |= !cell.has_resource();
+                    cell.minerals = true;
+                }
+            }
+        }
+        self.terrain_changed();
+        self.emit(SimEvent::MeteorStrike { x, y });
+    }
+
+    pub fn in_storm(&self, x: usize, y: usize) -> bool {
+        self.environment
+            .storms
+            .iter()
+            .any(|storm| self.distance(storm.center(), (x, y)) <= storm.radius)
+    }
+
+    // Le cœur d'une tempête est infranchissable
+    pub fn in_storm_core(&self, x: usize, y: usize) -> bool {
+        self.environment
+            .storms
+            .iter()
+            .any(|storm| self.distance(storm.center(), (x, y)) <= storm.radius / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::Cell;
+    use crate::station::StationStrategy;
+    use crate::testing;
+
+    type Weather = Vec<(f64, f64, u32)>;
+
+    // Fait évoluer l'environnement seul, sans les robots
+    fn evolve(seed: u32, ticks: u64) -> (Weather, Vec<Option<Cell>>) {
+        let mut map = testing::mission(seed, 32, StationStrategy::ResourceCentroid);
+        for _ in 0..ticks {
+            map.tick += 1;
+            map.update_environment();
+        }
+        let storms = map
+            .environment
+            .storms
+            .iter()
+            .map(|storm| (storm.x, storm.y, storm.remaining))
+            .collect();
+        let cells = map
+            .generated_cells()
+            .into_iter()
+            .map(|(x, y)| map.cell(x, y).copied())
+            .collect();
+        (storms, cells)
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_events() {
+        let (storms, cells) = evolve(9, 500);
+        assert_eq!(evolve(9, 500), (storms.clone(), cells.clone()));
+        // Assez de ticks pour qu'au moins un météore ait modifié le terrain
+        assert_ne!(evolve(9, 0).1, cells);
+    }
+
+    #[test]
+    fn energy_grows_back_after_the_delay() {
+        let mut map = testing::mission(9, 32, StationStrategy::ResourceCentroid);
+        let (x, y) = map
+            .generated_cells()
+            .into_iter()
+            .find(|&(x, y)| !map.is_obstacle(x, y) && !map.has_energy(x, y))
+            .unwrap();
+        map.schedule_regrowth(x, y);
+
+        map.tick += REGROWTH_DELAY - 1;
+        map.regrow_deposits();
+        assert!(!map.has_energy(x, y));
+        map.tick += 1;
+        map.regrow_deposits();
+        let cell = map.cell(x, y).unwrap();
+        assert!(cell.energy && cell.renewed);
+        assert!(map.environment.regrowth.is_empty());
+    }
+}
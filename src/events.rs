@@ -0,0 +1,235 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::chunk::ResourceKind;
+use crate::editor::Brush;
+use crate::mission::MissionOutcome;
+use crate::Map;
+use crate::EVENT_LOG_SIZE;
+
+// Ce qui se passe pendant la mission, tel que le voient les sorties du journal
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SimEvent {
+    ResourceFound {
+        kind: ResourceKind,
+        x: usize,
+        y: usize,
+    },
+    DepositReported {
+        kind: ResourceKind,
+        x: usize,
+        y: usize,
+        size: usize,
+    },
+    JobAssigned {
+        kind: ResourceKind,
+        x: usize,
+        y: usize,
+    },
+    ResourceCollected {
+        kind: ResourceKind,
+        x: usize,
+        y: usize,
+    },
+    ResourceDelivered {
+        kind: ResourceKind,
+        remaining: usize,
+    },
+    DepositVanished {
+        x: usize,
+        y: usize,
+    },
+    EnergyRegrown {
+        x: usize,
+        y: usize,
+    },
+    StormFormed {
+        x: usize,
+        y: usize,
+    },
+    MeteorStrike {
+        x: usize,
+        y: usize,
+    },
+    CellPainted {
+        brush: Brush,
+        x: usize,
+        y: usize,
+    },
+    MissionEnded {
+        outcome: MissionOutcome,
+    },
+}
+
+impl fmt::Display for SimEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimEvent::ResourceFound { kind, x, y } => {
+                write!(f, "Found {} at ({}, {}).", kind, x, y)
+            }
+            SimEvent::DepositReported { kind, x, y, size } => write!(
+                f,
+                "Explorer reported the {} deposit at ({}, {}), estimated size {}.",
+                kind, x, y, size
+            ),
+            SimEvent::JobAssigned { kind, x, y } => write!(
+                f,
+                "Station sent the extractor to the {} deposit at ({}, {}).",
+                kind, x, y
+            ),
+            SimEvent::ResourceCollected { kind, x, y } => {
+                write!(f, "Collected {} at ({}, {}).", kind, x, y)
+            }
+            SimEvent::ResourceDelivered { kind, remaining } => write!(
+                f,
+                "Extractor delivered {} to the station. Remaining resources: {}",
+                kind, remaining
+            ),
+            SimEvent::DepositVanished { x, y } => {
+                write!(f, "Deposit at ({}, {}) vanished.", x, y)
+            }
+            SimEvent::EnergyRegrown { x, y } => write!(f, "Energy regrown at ({}, {}).", x, y),
+            SimEvent::StormFormed { x, y } => write!(f, "Dust storm formed at ({}, {}).", x, y),
+            SimEvent::MeteorStrike { x, y } => write!(f, "Meteor strike at ({}, {}).", x, y),
+            SimEvent::CellPainted { brush, x, y } => {
+                write!(f, "Painted {} at ({}, {}).", brush, x, y)
+            }
+            SimEvent::MissionEnded { outcome } if outcome.is_success() => {
+                write!(f, "Mission complete: {}.", outcome)
+            }
+            SimEvent::MissionEnded { outcome } => write!(f, "Mission aborted: {}.", outcome),
+        }
+    }
+}
+
+// Niveau de détail d'une sortie du journal ; chaque événement n'apparaît
+// qu'à partir d'un certain niveau
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl FromStr for Verbosity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "quiet" => Ok(Verbosity::Quiet),
+            "normal" => Ok(Verbosity::Normal),
+            "verbose" => Ok(Verbosity::Verbose),
+            _ => Err(format!("unknown verbosity '{}'", s)),
+        }
+    }
+}
+
+impl SimEvent {
+    pub fn verbosity(&self) -> Verbosity {
+        match self {
+            SimEvent::MissionEnded { .. } => Verbosity::Quiet,
+            SimEvent::ResourceCollected { .. }
+            | SimEvent::EnergyRegrown { .. }
+            | SimEvent::CellPainted { .. } => Verbosity::Verbose,
+            _ => Verbosity::Normal,
+        }
+    }
+}
+
+// Sortie supplémentaire du journal, en plus de la console et du HUD
+pub trait EventSink: Send {
+    fn write(&mut self, tick: u64, event: &SimEvent) -> io::Result<()>;
+}
+
+// Un objet JSON par ligne : {"tick": 12, "event": "storm_formed", "x": 3, "y": 4}
+pub struct JsonLinesSink {
+    writer: BufWriter<File>,
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    tick: u64,
+    #[serde(flatten)]
+    event: &'a SimEvent,
+}
+
+impl JsonLinesSink {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(JsonLinesSink {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl EventSink for JsonLinesSink {
+    fn write(&mut self, tick: u64, event: &SimEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &JsonLine { tick, event })?;
+        writeln!(self.writer)
+    }
+}
+
+// Distribue les événements aux sorties du journal selon leur niveau de détail
+pub struct EventBus {
+    // Sortie standard ; coupée quand l'interface en mode texte occupe le terminal
+    pub console: Option<Verbosity>,
+    pub hud: Verbosity,
+    // Derniers événements affichés par le HUD
+    pub recent: VecDeque<(u64, SimEvent)>,
+    sinks: Vec<(Verbosity, Box<dyn EventSink>)>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus {
+            console: Some(Verbosity::Normal),
+            hud: Verbosity::Normal,
+            recent: VecDeque::new(),
+            sinks: vec![],
+        }
+    }
+}
+
+impl EventBus {
+    pub fn add_sink(&mut self, verbosity: Verbosity, sink: impl EventSink + 'static) {
+        self.sinks.push((verbosity, Box::new(sink)));
+    }
+
+    pub fn emit(&mut self, tick: u64, event: SimEvent) {
+        let level = event.verbosity();
+        if self.console.is_some_and(|verbosity| level <= verbosity) {
+            println!("{}", event);
+        }
+        // Une sortie en erreur (disque plein...) est abandonnée plutôt que d'arrêter la mission
+        self.sinks.retain_mut(|(verbosity, sink)| {
+            if level > *verbosity {
+                return true;
+            }
+            match sink.write(tick, &event) {
+                Ok(()) => true,
+                Err(err) => {
+                    eprintln!("Event log disabled: {}", err);
+                    false
+                }
+            }
+        });
+        if level <= self.hud {
+            self.recent.push_back((tick, event));
+            if self.recent.len() > EVENT_LOG_SIZE {
+                self.recent.pop_front();
+            }
+        }
+    }
+}
+
+impl Map {
+    pub fn emit(&mut self, event: SimEvent) {
+        self.metrics.observe(self.tick, &event);
+        self.events.emit(self.tick, event);
+    }
+}
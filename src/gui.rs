@@ -0,0 +1,516 @@
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics::{self, Color, DrawMode, DrawParam, MeshBuilder, Rect};
+use ggez::{conf, event, timer, Context, ContextBuilder, GameResult};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::animation::Animator;
+use crate::camera::Camera;
+use crate::clock::Clock;
+use crate::editor::Brush;
+use crate::heatmap::Heatmap;
+use crate::hud;
+use crate::inspector::{self, Selection};
+use crate::layout::Layout;
+use crate::map::Visibility;
+use crate::minimap;
+use crate::overlay::Overlays;
+use crate::replay::Replay;
+use crate::robot::RobotId;
+use crate::sprites::{SpriteKind, Sprites, TERRAIN_SPRITES};
+use crate::Map;
+use crate::ASSETS_DIR_VAR;
+use crate::{
+    CELL_SIZE, CLICK_TOLERANCE, PAN_STEP, QUICKSAVE_PATH, REMEMBERED_SHADE, SEEK_STEP, ZOOM_STEP,
+};
+use crate::{
+    DEFAULT_COLOR, ENERGY_COLOR, FOG_COLOR, MINERALS_COLOR, OBSTACLE_COLOR, ROBOT_EXPLORER_COLOR,
+    ROBOT_EXTRACTOR_COLOR, SCIENCE_INTERESTS_COLOR, STATION_COLOR, STORM_COLOR,
+};
+use crate::{MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Fenêtre graphique : la simulation et les ressources nécessaires à son affichage
+pub struct Gui {
+    pub map: Map,
+    sprites: Sprites,
+    camera: Camera,
+    clock: Clock,
+    animator: Animator,
+    selected: RobotId,
+    show_hud: bool,
+    show_minimap: bool,
+    overlays: Overlays,
+    heatmap: Option<Heatmap>,
+    inspected: Option<Selection>,
+    // Mode édition : pinceau courant, et pinceau appliqué tant qu'un bouton est enfoncé
+    editing: bool,
+    brush: Brush,
+    painting: Option<Brush>,
+    // Distance parcourue par la souris depuis l'appui, pour distinguer un clic d'un glissé
+    drag_distance: f32,
+    // Glissé commencé sur la minimap : la caméra suit la souris
+    minimap_dragging: bool,
+    // Partie enregistrée en cours de lecture
+    replay: Option<Arc<Replay>>,
+}
+
+impl Gui {
+    pub fn new(ctx: &mut Context, map: Map, replay: Option<Arc<Replay>>) -> Self {
+        // La vue démarre centrée sur la station
+        let (station_x, station_y) = map.station().unwrap_or((0, 0));
+        let camera = Camera::new(map.topology.cell_center(station_x, station_y, CELL_SIZE));
+
+        Gui {
+            map,
+            sprites: Sprites::load(ctx),
+            camera,
+            clock: Clock::new(),
+            animator: Animator::default(),
+            selected: RobotId::Explorer(0),
+            show_hud: true,
+            show_minimap: true,
+            overlays: Overlays::default(),
+            heatmap: None,
+            inspected: None,
+            editing: false,
+            brush: Brush::Obstacle,
+            painting: None,
+            drag_distance: 0.0,
+            minimap_dragging: false,
+            replay,
+        }
+    }
+
+    fn layout(&self, ctx: &Context) -> Layout {
+        Layout::new(
+            graphics::screen_coordinates(ctx),
+            self.show_hud,
+            self.inspected.is_some(),
+            self.show_minimap,
+            self.map.height as f32 / self.map.width as f32,
+        )
+    }
+
+    // Case de la carte sous un point de l'écran, hors panneaux et minimap
+    fn cell_under(&self, ctx: &Context, x: f32, y: f32) -> Option<(usize, usize)> {
+        let layout = self.layout(ctx);
+        if !layout.view.contains([x, y]) || layout.minimap.is_some_and(|area| area.contains([x, y]))
+        {
+            return None;
+        }
+        let world = self.camera.to_world([x, y], layout.view);
+        self.map
+            .topology
+            .cell_at(world, CELL_SIZE, self.map.width, self.map.height)
+    }
+
+    // Un clic sur un robot l'inspecte et le sélectionne, sinon la case est inspectée
+    fn inspect(&mut self, ctx: &Context, x: f32, y: f32) {
+        let Some(cell) = self.cell_under(ctx, x, y) else {
+            self.inspected = None;
+            return;
+        };
+        self.inspected = match self.map.robot_at(cell.0, cell.1) {
+            Some(id) => {
+                self.selected = id;
+                Some(Selection::Robot(id))
+            }
+            None => Some(Selection::Cell(cell.0, cell.1)),
+        };
+    }
+
+    // Centre la caméra sur le point de la minimap sous la souris ; renvoie false
+    // si la souris n'est pas sur la minimap
+    fn jump_to(&mut self, ctx: &Context, x: f32, y: f32) -> bool {
+        let Some(area) = self.layout(ctx).minimap else {
+            return false;
+        };
+        match minimap::world_point(&self.map, area, [x, y]) {
+            Some(point) => {
+                self.camera.center_on(point);
+                self.camera.follow = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Déplacement dans la partie enregistrée ; les animations en cours n'ont plus de sens
+    fn seek(&mut self, tick: u64) {
+        if let Some(replay) = self.replay.clone() {
+            replay.seek(&mut self.map, tick);
+            self.animator = Animator::default();
+        }
+    }
+
+    // Sauvegarde rapide de la mission ; une partie relue se parcourt avec seek
+    fn quicksave(&self) {
+        let path = Path::new(QUICKSAVE_PATH);
+        match self.map.save_snapshot(path) {
+            Ok(()) => println!(
+                "Snapshot saved to {} at tick {}",
+                path.display(),
+                self.map.tick
+            ),
+            Err(err) => eprintln!("Cannot save snapshot: {}", err),
+        }
+    }
+
+    // Reprend la dernière sauvegarde rapide ; les sorties du journal restent branchées
+    fn quickload(&mut self) {
+        match Map::load_snapshot(Path::new(QUICKSAVE_PATH)) {
+            Ok(mut map) => {
+                map.events = std::mem::take(&mut self.map.events);
+                self.map = map;
+                self.animator = Animator::default();
+                println!("Snapshot loaded at tick {}", self.map.tick);
+            }
+            Err(err) => eprintln!("Cannot load snapshot: {}", err),
+        }
+    }
+
+    fn paint(&mut self, ctx: &Context, x: f32, y: f32) {
+        if let (Some(brush), Some((cell_x, cell_y))) = (self.painting, self.cell_under(ctx, x, y)) {
+            self.map.paint(cell_x, cell_y, brush);
+        }
+    }
+}
+
+// Dossier des ressources : ASSETS_DIR_VAR s'il est défini, sinon le premier
+// dossier contenant resources/ en remontant depuis l'exécutable
+fn asset_root() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(ASSETS_DIR_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    let exe = std::env::current_exe().ok()?;
+    exe.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("resources").is_dir())
+        .map(Path::to_path_buf)
+}
+
+// Ouvre la fenêtre et rend la simulation une fois la fenêtre fermée
+pub fn run(map: Map, replay: Option<Arc<Replay>>) -> GameResult<Map> {
+    let mut cb = ContextBuilder::new("CosmoBots", "Team CosmoBots")
+        .window_setup(conf::WindowSetup::default().title("CosmoBots"))
+        .window_mode(
+            conf::WindowMode::default()
+                .dimensions(WINDOW_WIDTH, WINDOW_HEIGHT)
+                .min_dimensions(MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT)
+                .resizable(true),
+        );
+
+    // Textures : resources/ et assets/textures/ du dossier des ressources
+    if let Some(root) = asset_root() {
+        cb = cb
+            .add_resource_path(root.join("resources"))
+            .add_resource_path(root.join("assets").join("textures"));
+    }
+
+    let (ctx, event_loop) = &mut cb.build()?;
+    let mut gui = Gui::new(ctx, map, replay);
+    event::run(ctx, event_loop, &mut gui)?;
+    Ok(gui.map)
+}
+
+// Assombrit une couleur selon l'altitude de la case
+fn shaded(color: Color, shade: f32) -> Color {
+    Color::new(color.r * shade, color.g * shade, color.b * shade, color.a)
+}
+
+impl event::EventHandler for Gui {
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        graphics::clear(ctx, graphics::BLACK);
+
+        let alpha = self.clock.alpha();
+
+        // Suivi du robot sélectionné
+        if let Some(id) = self.camera.follow {
+            if let Some((center, _)) = self.animator.robot_pose(&self.map, id, alpha) {
+                self.camera.center_on(center);
+            }
+        }
+
+        let layout = self.layout(ctx);
+        graphics::push_transform(ctx, Some(self.camera.transform(layout.view).to_matrix()));
+        graphics::apply_transformations(ctx)?;
+
+        let map = &self.map;
+        let station = map.station();
+
+        let all_resources_collected = map.count_consumables() == 0;
+
+        // Seules les cases visibles sont dessinées
+        let (columns, rows) = self.camera.visible_cells(map, layout.view);
+
+        // Les cases sans texture et les surcouches sont regroupées dans deux maillages
+        let mut cells = MeshBuilder::new();
+        let mut has_cells = false;
+        let mut overlays = MeshBuilder::new();
+        let mut has_overlays = false;
+
+        if let Some(heatmap) = self.heatmap {
+            has_overlays = heatmap.build(map, columns.clone(), rows.clone(), &mut overlays)?;
+        }
+
+        for y in rows {
+            for x in columns.clone() {
+                let center = map.topology.cell_center(x, y, CELL_SIZE);
+                let outline = map.topology.cell_outline(center, CELL_SIZE);
+
+                // Les cases sont dessinées telles que les robots les connaissent
+                let Some(observed) = map.observed(x, y) else {
+                    cells.polygon(DrawMode::fill(), &outline, FOG_COLOR)?;
+                    has_cells = true;
+                    continue;
+                };
+
+                let (kind, cell_color) = if all_resources_collected && station == Some((x, y)) {
+                    (SpriteKind::Station, STATION_COLOR)
+                } else if observed.obstacle {
+                    (SpriteKind::Obstacle, OBSTACLE_COLOR)
+                } else if observed.energy {
+                    (SpriteKind::Energy, ENERGY_COLOR)
+                } else if observed.minerals {
+                    (SpriteKind::Minerals, MINERALS_COLOR)
+                } else if observed.science_interests {
+                    (SpriteKind::ScienceInterests, SCIENCE_INTERESTS_COLOR)
+                } else {
+                    (SpriteKind::Ground, DEFAULT_COLOR)
+                };
+
+                // Les cases basses sont plus sombres que les sommets, et les cases
+                // hors de portée des capteurs plus sombres encore
+                let mut shade = 0.7 + 0.3 * map.elevation(x, y) as f32;
+                if map.visibility(x, y) == Visibility::Remembered {
+                    shade *= REMEMBERED_SHADE;
+                }
+                let tint = shaded(graphics::WHITE, shade);
+                if !self.sprites.add(kind, center, CELL_SIZE, 0.0, tint) {
+                    cells.polygon(DrawMode::fill(), &outline, shaded(cell_color, shade))?;
+                    has_cells = true;
+                }
+
+                if map.in_storm(x, y) {
+                    overlays.polygon(DrawMode::fill(), &outline, STORM_COLOR)?;
+                    has_overlays = true;
+                }
+            }
+        }
+
+        if has_cells {
+            let mesh = cells.build(ctx)?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+        self.sprites.draw(ctx, &TERRAIN_SPRITES)?;
+        if has_overlays {
+            let mesh = overlays.build(ctx)?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        let mut debug = MeshBuilder::new();
+        if self.overlays.build(map, &mut debug)? {
+            let mesh = debug.build(ctx)?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        let mut effects = MeshBuilder::new();
+        if self.animator.build_effects(map, &mut effects) {
+            let mesh = effects.build(ctx)?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        for id in map.robot_ids() {
+            let color = match id {
+                RobotId::Explorer(_) => ROBOT_EXPLORER_COLOR,
+                RobotId::Extractor(_) => ROBOT_EXTRACTOR_COLOR,
+            };
+            let Some((center, rotation)) = self.animator.robot_pose(map, id, alpha) else {
+                continue;
+            };
+            // La texture du robot est teintée de la couleur de son type
+            if !self
+                .sprites
+                .add(SpriteKind::Robot, center, CELL_SIZE, rotation, color)
+            {
+                let robot_circle = graphics::Mesh::new_circle(
+                    ctx,
+                    DrawMode::fill(),
+                    center,
+                    CELL_SIZE / 2.0,
+                    1.0,
+                    color,
+                )?;
+                graphics::draw(ctx, &robot_circle, DrawParam::default())?;
+            }
+        }
+        self.sprites.draw(ctx, &[SpriteKind::Robot])?;
+
+        graphics::pop_transform(ctx);
+        graphics::apply_transformations(ctx)?;
+
+        if let Some(area) = layout.minimap {
+            minimap::draw(ctx, &self.map, &self.camera, layout.view, area)?;
+        }
+        if let Some(area) = layout.hud {
+            let mut modes = vec![];
+            if let Some(replay) = &self.replay {
+                modes.push(match self.map.replay_diverged() {
+                    Some(tick) => format!("Replay diverged at tick {}", tick),
+                    None => format!("Replay: tick {} / {}", self.map.tick, replay.length),
+                });
+            }
+            if self.editing {
+                modes.push(format!("Edit mode, brush: {}", self.brush));
+            }
+            if let Some(heatmap) = self.heatmap {
+                modes.push(format!("Heatmap: {}", heatmap));
+            }
+            hud::draw(ctx, &self.map, &self.clock, &modes, area)?;
+        }
+        if let (Some(selection), Some(area)) = (self.inspected, layout.inspector) {
+            inspector::draw(ctx, &self.map, selection, area)?;
+        }
+
+        graphics::present(ctx)?;
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let map = &mut self.map;
+        let animator = &mut self.animator;
+        self.clock.advance(timer::delta(ctx), || {
+            if !map.running() {
+                return false;
+            }
+            animator.before_tick(map);
+            map.update_robot();
+            animator.after_tick(map);
+            true
+        });
+        Ok(())
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        let step = PAN_STEP * CELL_SIZE * self.camera.zoom;
+        match keycode {
+            KeyCode::Escape => event::quit(ctx),
+            KeyCode::Left | KeyCode::A => self.camera.pan(step, 0.0),
+            KeyCode::Right | KeyCode::D => self.camera.pan(-step, 0.0),
+            KeyCode::Up | KeyCode::W => self.camera.pan(0.0, step),
+            KeyCode::Down | KeyCode::S => self.camera.pan(0.0, -step),
+            // Tab change de robot, F active ou coupe son suivi
+            KeyCode::Tab => {
+                self.selected = self.map.next_robot(self.selected);
+                if self.camera.follow.is_some() {
+                    self.camera.follow = Some(self.selected);
+                }
+            }
+            KeyCode::F => {
+                self.camera.follow = match self.camera.follow {
+                    Some(_) => None,
+                    None => Some(self.selected),
+                };
+            }
+            KeyCode::H => self.show_hud = !self.show_hud,
+            KeyCode::M => self.show_minimap = !self.show_minimap,
+            // Contrôle de la vitesse de la simulation
+            KeyCode::Space => self.clock.toggle_pause(),
+            KeyCode::N | KeyCode::Period => self.clock.step(),
+            KeyCode::Add | KeyCode::Equals => self.clock.faster(),
+            KeyCode::Subtract | KeyCode::Minus => self.clock.slower(),
+            // Surcouches de débogage
+            KeyCode::F1 => self.overlays.paths = !self.overlays.paths,
+            KeyCode::F2 => self.overlays.targets = !self.overlays.targets,
+            KeyCode::F3 => self.overlays.sensors = !self.overlays.sensors,
+            KeyCode::F4 => self.overlays.search = !self.overlays.search,
+            KeyCode::F5 => self.overlays.links = !self.overlays.links,
+            KeyCode::V => self.heatmap = Heatmap::cycle(self.heatmap),
+            // Lecture d'une partie enregistrée : retour en arrière, avance rapide, début
+            KeyCode::LBracket => self.seek(self.map.tick.saturating_sub(SEEK_STEP)),
+            KeyCode::RBracket => self.seek(self.map.tick + SEEK_STEP),
+            KeyCode::Home => self.seek(0),
+            // Sauvegarde et reprise rapides, hors lecture d'une partie enregistrée
+            KeyCode::F6 if self.replay.is_none() => self.quicksave(),
+            KeyCode::F9 if self.replay.is_none() => self.quickload(),
+            // Mode édition et choix du pinceau ; une partie relue ne peut pas être modifiée
+            KeyCode::E if self.replay.is_none() => self.editing = !self.editing,
+            KeyCode::Key1 => self.brush = Brush::Obstacle,
+            KeyCode::Key2 => self.brush = Brush::Energy,
+            KeyCode::Key3 => self.brush = Brush::Minerals,
+            KeyCode::Key4 => self.brush = Brush::Erase,
+            _ => (),
+        }
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        // En mode édition le bouton gauche peint et le droit efface ;
+        // sinon le bouton gauche déplace la vue ou inspecte
+        if button == MouseButton::Left && self.jump_to(ctx, x, y) {
+            self.minimap_dragging = true;
+            return;
+        }
+        // Les clics sur les panneaux latéraux ne concernent pas la carte
+        if !self.layout(ctx).view.contains([x, y]) {
+            return;
+        }
+        match (self.editing, button) {
+            (true, MouseButton::Left) => self.painting = Some(self.brush),
+            (true, MouseButton::Right) => self.painting = Some(Brush::Erase),
+            (false, MouseButton::Left) => {
+                self.camera.dragging = true;
+                self.drag_distance = 0.0;
+            }
+            (false, MouseButton::Right) => self.inspected = None,
+            _ => (),
+        }
+        self.paint(ctx, x, y);
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        self.minimap_dragging = false;
+        match button {
+            MouseButton::Left if self.camera.dragging => {
+                self.camera.dragging = false;
+                if self.drag_distance < CLICK_TOLERANCE {
+                    self.inspect(ctx, x, y);
+                }
+            }
+            MouseButton::Left | MouseButton::Right => self.painting = None,
+            _ => (),
+        }
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.minimap_dragging {
+            self.jump_to(ctx, x, y);
+            return;
+        }
+        if self.camera.dragging {
+            self.drag_distance += dx.abs() + dy.abs();
+            // Les petits tremblements d'un clic ne déplacent pas la vue
+            if self.drag_distance >= CLICK_TOLERANCE {
+                self.camera.pan(dx, dy);
+            }
+        }
+        self.paint(ctx, x, y);
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) {
+        let factor = if y > 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+        let mouse = ggez::input::mouse::position(ctx);
+        let view = self.layout(ctx).view;
+        self.camera.zoom_at(factor, [mouse.x, mouse.y], view);
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        // La vue suit la taille de la fenêtre au lieu d'être étirée
+        let _ = graphics::set_screen_coordinates(ctx, Rect::new(0.0, 0.0, width, height));
+    }
+}
@@ -0,0 +1,95 @@
+use ggez::graphics::{Color, DrawMode, MeshBuilder};
+use ggez::GameResult;
+use std::fmt;
+use std::ops::Range;
+
+use crate::Map;
+use crate::{CELL_SIZE, HEATMAP_ALPHA};
+
+// Cartes de chaleur affichables par-dessus le terrain
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Heatmap {
+    Visits,
+    Discovery,
+    ExplorerTraffic,
+    ExtractorTraffic,
+}
+
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Heatmap::Visits => "visits",
+            Heatmap::Discovery => "first discovery",
+            Heatmap::ExplorerTraffic => "explorer traffic",
+            Heatmap::ExtractorTraffic => "extractor traffic",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Heatmap {
+    // Carte suivante dans le cycle ; None les masque toutes
+    pub fn cycle(current: Option<Heatmap>) -> Option<Heatmap> {
+        match current {
+            None => Some(Heatmap::Visits),
+            Some(Heatmap::Visits) => Some(Heatmap::Discovery),
+            Some(Heatmap::Discovery) => Some(Heatmap::ExplorerTraffic),
+            Some(Heatmap::ExplorerTraffic) => Some(Heatmap::ExtractorTraffic),
+            Some(Heatmap::ExtractorTraffic) => None,
+        }
+    }
+
+    // Valeurs non nulles de la carte, indexées par case
+    fn values(&self, map: &Map) -> Vec<((usize, usize), f64)> {
+        let history = &map.history;
+        match self {
+            Heatmap::Discovery => history
+                .discovered_at
+                .iter()
+                .map(|(&cell, &tick)| (cell, tick as f64))
+                .collect(),
+            _ => history
+                .traffic
+                .iter()
+                .map(|(&cell, traffic)| {
+                    let count = match self {
+                        Heatmap::ExplorerTraffic => traffic.explorer,
+                        Heatmap::ExtractorTraffic => traffic.extractor,
+                        _ => traffic.visits(),
+                    };
+                    (cell, count as f64)
+                })
+                .filter(|&(_, count)| count > 0.0)
+                .collect(),
+        }
+    }
+
+    // Ajoute les cases visibles au maillage, du bleu (faible) au rouge (élevé) ;
+    // renvoie false s'il est resté vide
+    pub fn build(
+        &self,
+        map: &Map,
+        columns: Range<usize>,
+        rows: Range<usize>,
+        mesh: &mut MeshBuilder,
+    ) -> GameResult<bool> {
+        let values = self.values(map);
+        // L'échelle porte sur toute la carte pour ne pas changer avec la vue
+        let max = values.iter().map(|&(_, value)| value).fold(0.0, f64::max);
+        let mut drawn = false;
+
+        for ((x, y), value) in values {
+            if !columns.contains(&x) || !rows.contains(&y) {
+                continue;
+            }
+            let ratio = if max > 0.0 { (value / max) as f32 } else { 0.0 };
+            let color = Color::new(ratio, 0.2, 1.0 - ratio, HEATMAP_ALPHA);
+            let center = map.topology.cell_center(x, y, CELL_SIZE);
+            let outline = map.topology.cell_outline(center, CELL_SIZE);
+            mesh.polygon(DrawMode::fill(), &outline, color)?;
+            drawn = true;
+        }
+
+        Ok(drawn)
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::robot::RobotId;
+use crate::snapshot::pairs;
+use crate::Map;
+
+// Passages sur une case, par type de robot
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Traffic {
+    pub explorer: u32,
+    pub extractor: u32,
+}
+
+impl Traffic {
+    pub fn visits(&self) -> u32 {
+        self.explorer + self.extractor
+    }
+}
+
+// Historique de la mission case par case, utilisé par les cartes de chaleur
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(with = "pairs")]
+    pub traffic: HashMap<(usize, usize), Traffic>,
+    #[serde(with = "pairs")]
+    pub discovered_at: HashMap<(usize, usize), u64>,
+}
+
+impl Map {
+    // Compte l'entrée d'un robot sur une case
+    pub fn record_visit(&mut self, id: RobotId, x: usize, y: usize) {
+        let traffic = self.history.traffic.entry((x, y)).or_default();
+        match id {
+            RobotId::Explorer(_) => traffic.explorer += 1,
+            RobotId::Extractor(_) => traffic.extractor += 1,
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use ggez::graphics::{self, Align, DrawMode, DrawParam, Mesh, Rect, Text};
+use ggez::{Context, GameResult};
+
+use crate::clock::Clock;
+use crate::robot::RobotId;
+use crate::Map;
+use crate::PANEL_COLOR;
+
+const MARGIN: f32 = 8.0;
+
+// Lignes du HUD : statistiques de la mission, état des robots et derniers événements
+pub fn lines(map: &Map, clock: &Clock) -> Vec<String> {
+    let speed = if clock.paused {
+        "paused".to_string()
+    } else {
+        clock.speed().to_string()
+    };
+
+    let mut lines = vec![
+        format!("Tick: {} ({})", map.tick, speed),
+        match map.outcome {
+            Some(outcome) => format!("Mission ended: {}", outcome),
+            None => "Mission in progress".to_string(),
+        },
+        format!("Remaining consumables: {}", map.count_consumables()),
+        format!("Explored: {:.1}%", map.explored_fraction() * 100.0),
+        format!(
+            "Station stockpile: {} energy, {} minerals",
+            map.stockpile.energy, map.stockpile.minerals
+        ),
+        format!(
+            "Station backlog: {} jobs, {} units",
+            map.backlog.jobs.len(),
+            map.backlog.units()
+        ),
+    ];
+
+    for (index, explorer) in map.robot_explorers.iter().enumerate() {
+        lines.push(format!(
+            "{} ({}, {}): {}",
+            RobotId::Explorer(index),
+            explorer.x,
+            explorer.y,
+            explorer.status()
+        ));
+    }
+    for (index, extractor) in map.robot_extractors.iter().enumerate() {
+        lines.push(format!(
+            "{} ({}, {}): {}",
+            RobotId::Extractor(index),
+            extractor.x,
+            extractor.y,
+            extractor.status()
+        ));
+    }
+
+    if !map.events.recent.is_empty() {
+        lines.push(String::new());
+        lines.extend(
+            map.events
+                .recent
+                .iter()
+                .map(|(tick, event)| format!("[{}] {}", tick, event)),
+        );
+    }
+
+    lines
+}
+
+// Dessiné dans le panneau latéral gauche ;
+// `modes` décrit les modes d'affichage actifs de la fenêtre
+pub fn draw(
+    ctx: &mut Context,
+    map: &Map,
+    clock: &Clock,
+    modes: &[String],
+    area: Rect,
+) -> GameResult {
+    let mut text = modes.to_vec();
+    text.extend(lines(map, clock));
+    draw_panel(ctx, &text, area)
+}
+
+// Panneau de texte occupant `area` ; les lignes trop longues sont coupées
+pub fn draw_panel(ctx: &mut Context, lines: &[String], area: Rect) -> GameResult {
+    let mut text = Text::new(lines.join("\n"));
+    text.set_bounds([area.w - MARGIN * 2.0, f32::INFINITY], Align::Left);
+
+    let background = Mesh::new_rectangle(ctx, DrawMode::fill(), area, PANEL_COLOR)?;
+    graphics::draw(ctx, &background, DrawParam::default())?;
+    graphics::draw(
+        ctx,
+        &text,
+        DrawParam::default().dest([area.x + MARGIN, area.y + MARGIN]),
+    )
+}
@@ -0,0 +1,113 @@
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+
+use crate::hud::draw_panel;
+use crate::robot::RobotId;
+use crate::Map;
+
+// Élément cliqué dont l'inspecteur affiche l'état
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Selection {
+    Robot(RobotId),
+    Cell(usize, usize),
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn position(position: Option<(usize, usize)>) -> String {
+    match position {
+        Some((x, y)) => format!("({}, {})", x, y),
+        None => "none".to_string(),
+    }
+}
+
+fn robot_lines(map: &Map, id: RobotId) -> Vec<String> {
+    match id {
+        RobotId::Explorer(index) => match map.robot_explorers.get(index) {
+            Some(explorer) => vec![
+                id.to_string(),
+                format!("Position: ({}, {})", explorer.x, explorer.y),
+                format!("Station: ({}, {})", explorer.station_x, explorer.station_y),
+                format!("Status: {}", explorer.status()),
+                format!(
+                    "Deposits to report: {} ({} units)",
+                    explorer.deposits.len(),
+                    explorer.units_found()
+                ),
+                format!("Reporting: {}", yes_no(explorer.reporting)),
+                format!("Energy used: {}", explorer.energy_used),
+                format!("Planned path: {} cells", explorer.path.len()),
+            ],
+            None => vec![format!("{}: not deployed", id)],
+        },
+        RobotId::Extractor(index) => match map.robot_extractors.get(index) {
+            Some(extractor) => vec![
+                id.to_string(),
+                format!("Position: ({}, {})", extractor.x, extractor.y),
+                format!(
+                    "Station: ({}, {})",
+                    extractor.station_x, extractor.station_y
+                ),
+                format!("Status: {}", extractor.status()),
+                format!(
+                    "Cargo: {}",
+                    extractor
+                        .cargo
+                        .map_or("none".to_string(), |kind| format!("{:?}", kind))
+                ),
+                format!("Target: {}", position(extractor.target_position)),
+                format!("Waiting: {}", yes_no(extractor.waiting)),
+                format!("Energy used: {}", extractor.energy_used),
+                format!("Planned path: {} cells", extractor.path.len()),
+            ],
+            None => vec![format!("{}: not deployed", id)],
+        },
+    }
+}
+
+fn cell_lines(map: &Map, x: usize, y: usize) -> Vec<String> {
+    let mut lines = vec![format!("Cell ({}, {})", x, y)];
+    match map.cell(x, y) {
+        Some(cell) => lines.extend([
+            format!("Elevation: {:.2}", cell.elevation),
+            format!("Obstacle: {}", yes_no(cell.obstacle)),
+            format!("Energy: {}", yes_no(cell.energy)),
+            format!("Minerals: {}", yes_no(cell.minerals)),
+            format!("Science interests: {}", yes_no(cell.science_interests)),
+            format!("Explored: {}", yes_no(cell.explored)),
+            format!("Visibility: {:?}", map.visibility(x, y)),
+            format!(
+                "Remembered: {}",
+                match cell.memory {
+                    Some(memory) => format!(
+                        "obstacle {}, energy {}, minerals {}",
+                        yes_no(memory.obstacle),
+                        yes_no(memory.energy),
+                        yes_no(memory.minerals)
+                    ),
+                    None => "nothing".to_string(),
+                }
+            ),
+            format!("Station: {}", yes_no(map.station() == Some((x, y)))),
+            format!("In storm: {}", yes_no(map.in_storm(x, y))),
+            format!("Blocked: {}", yes_no(map.is_blocked(x, y))),
+        ]),
+        None => lines.push("Not generated yet".to_string()),
+    }
+    lines
+}
+
+// Dessiné dans le panneau latéral droit
+pub fn draw(ctx: &mut Context, map: &Map, selection: Selection, area: Rect) -> GameResult {
+    let lines = match selection {
+        Selection::Robot(id) => robot_lines(map, id),
+        Selection::Cell(x, y) => cell_lines(map, x, y),
+    };
+    draw_panel(ctx, &lines, area)
+}
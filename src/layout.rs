@@ -0,0 +1,53 @@
+use ggez::graphics::Rect;
+
+use crate::{MINIMAP_MARGIN, MINIMAP_SIZE, SIDE_PANEL_WIDTH};
+
+// Découpage de la fenêtre : HUD à gauche, inspecteur à droite, carte au centre
+// et minimap dans le coin inférieur droit de la carte
+pub struct Layout {
+    pub view: Rect,
+    pub hud: Option<Rect>,
+    pub inspector: Option<Rect>,
+    pub minimap: Option<Rect>,
+}
+
+impl Layout {
+    // `world_ratio` est le rapport hauteur / largeur du monde, conservé par la minimap
+    pub fn new(
+        screen: Rect,
+        show_hud: bool,
+        show_inspector: bool,
+        show_minimap: bool,
+        world_ratio: f32,
+    ) -> Self {
+        let panel = |x: f32| Rect::new(x, screen.y, SIDE_PANEL_WIDTH, screen.h);
+
+        let hud = show_hud.then(|| panel(screen.x));
+        let inspector = show_inspector.then(|| panel(screen.right() - SIDE_PANEL_WIDTH));
+
+        let left = hud.map_or(screen.x, |rect| rect.right());
+        let right = inspector.map_or(screen.right(), |rect| rect.x);
+        let view = Rect::new(left, screen.y, (right - left).max(0.0), screen.h);
+
+        let minimap = show_minimap.then(|| {
+            let (width, height) = if world_ratio > 1.0 {
+                (MINIMAP_SIZE / world_ratio, MINIMAP_SIZE)
+            } else {
+                (MINIMAP_SIZE, MINIMAP_SIZE * world_ratio)
+            };
+            Rect::new(
+                view.right() - width - MINIMAP_MARGIN,
+                view.bottom() - height - MINIMAP_MARGIN,
+                width,
+                height,
+            )
+        });
+
+        Layout {
+            view,
+            hud,
+            inspector,
+            minimap,
+        }
+    }
+}
@@ -1,32 +1,153 @@
+use clap::Parser;
 use ggez::graphics::Color;
-use ggez::ContextBuilder;
-use ggez::{conf, event, GameResult};
-use std::cmp::Ordering;
+use std::process::ExitCode;
+use std::time::Duration;
 
+mod animation;
+mod backlog;
+mod batch;
+mod camera;
+mod chunk;
+mod cli;
+mod clock;
+mod editor;
+mod environment;
+mod events;
+mod gui;
+mod heatmap;
+mod history;
+mod hud;
+mod inspector;
+mod layout;
 mod map;
+mod mapfile;
+mod metrics;
+mod minimap;
+mod mission;
+mod overlay;
+mod pathfinding;
+mod replay;
 mod robot;
+mod scenario;
+mod snapshot;
+mod sprites;
+mod station;
+#[cfg(test)]
+mod testing;
+mod topology;
+mod tui;
 
+use crate::cli::Cli;
 use crate::map::Map;
 use crate::robot::{RobotExplorer, RobotExtractor};
 
 const MAP_SIZE: usize = 10;
+// La station a besoin de toutes ses cases voisines
+const MIN_MAP_SIZE: usize = 3;
+// Les chunks ne sont générés qu'une fois visités, mais au-delà de cette taille
+// les positions à l'écran (en f32) ne sont plus assez précises pour les cases
+const MAX_MAP_SIZE: usize = 1 << 20;
+// Mondes générés en entier d'un coup : gen-map, bench et fichiers de carte
+const MAX_FULL_MAP_SIZE: usize = 2048;
 const CELL_SIZE: f32 = 30.0;
 
+// Fenêtre redimensionnable : taille initiale et minimale, panneaux latéraux et minimap
+const WINDOW_WIDTH: f32 = 1280.0;
+const WINDOW_HEIGHT: f32 = 720.0;
+const MIN_WINDOW_WIDTH: f32 = 640.0;
+const MIN_WINDOW_HEIGHT: f32 = 480.0;
+const SIDE_PANEL_WIDTH: f32 = 280.0;
+const MINIMAP_SIZE: f32 = 160.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+
+// Cadence de la simulation : durée d'un tick à vitesse 1x, crans de vitesse
+// disponibles et limites par image pour garder l'affichage fluide
+const TICK_DURATION: Duration = Duration::from_secs(1);
+const SPEEDS: [f64; 8] = [0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0];
+const MAX_TICKS_PER_FRAME: u32 = 100;
+const MAX_SPEED_FRAME_BUDGET: Duration = Duration::from_millis(12);
+
+// Parties sans affichage : durée maximale d'une partie, parties par configuration
+// des expériences en lot, et missions mesurées par la sous-commande bench
+const HEADLESS_TICKS: u64 = 5000;
+const BATCH_RUNS: u32 = 10;
+const BENCH_TICKS: u64 = 2000;
+const BENCH_SIZE: usize = 64;
+const BENCH_RUNS: u32 = 3;
+
+// Fin de mission : ticks sans progrès avant l'abandon, et ticks sans pouvoir
+// bouger avant qu'un robot soit considéré comme bloqué (plus qu'une tempête)
+const STALL_TICKS: u64 = 1000;
+const STRANDED_TICKS: u64 = 50;
+
+// Flotte déployée par défaut et nombre maximal de robots de chaque type
+const EXPLORERS: usize = 1;
+const EXTRACTORS: usize = 1;
+const MAX_ROBOTS: usize = 16;
+
+// Unités estimées qu'un explorateur accumule avant de rentrer les signaler,
+// sauf si un extracteur attend déjà du travail
+const REPORT_UNITS: usize = 6;
+
+// Intervalle (en ticks) entre deux relevés des séries temporelles de la mission
+const METRICS_INTERVAL: u64 = 10;
+
+// Saut en avant ou en arrière (en ticks) pendant la lecture d'une partie enregistrée
+const SEEK_STEP: u64 = 100;
+
+// Variable d'environnement désignant le dossier des textures
+const ASSETS_DIR_VAR: &str = "COSMOBOTS_ASSETS";
+
+// Fichier de la sauvegarde rapide de la fenêtre graphique
+const QUICKSAVE_PATH: &str = "quicksave.snapshot";
+
+// Durée des animations d'extraction et de déchargement
+const ANIMATION_DURATION: Duration = Duration::from_millis(600);
+
+// Interface en mode texte : cadence d'affichage et largeur de la barre latérale
+const TUI_FRAME: Duration = Duration::from_millis(50);
+const TUI_SIDEBAR_WIDTH: u16 = 48;
+
+// Nombre d'événements récents gardés pour le HUD
+const EVENT_LOG_SIZE: usize = 6;
+
+// Caméra : déplacement au clavier (en cases) et bornes du zoom
+const PAN_STEP: f32 = 2.0;
+const ZOOM_STEP: f32 = 1.1;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 4.0;
+
+// Déplacement de la souris (en pixels) en dessous duquel un appui compte comme un clic
+const CLICK_TOLERANCE: f32 = 4.0;
+
+// Rayon des capteurs des robots et zone générée au lancement (en chunks)
+const SENSOR_RANGE: usize = 1;
+// Au-delà, chaque observation parcourt trop de cases
+const MAX_SENSOR_RANGE: usize = 16;
+const INITIAL_CHUNK_RADIUS: usize = 1;
+
 const OBSTACLE_THRESHOLD: f64 = 0.2;
 const ENERGY_THRESHOLD: f64 = 0.5;
 const MINERALS_THRESHOLD: f64 = 0.5;
 // const SCIENCE_INTERESTS_THRESHOLD: f64 = 0.5;
 
-const DIRECTIONS: [(isize, isize); 8] = [
-    (0, -1),
-    (0, 1),
-    (-1, 0),
-    (1, 0),
-    (-1, -1),
-    (1, -1),
-    (-1, 1),
-    (1, 1),
-];
+// Paramètres du relief
+const ELEVATION_SCALE: f64 = 0.15;
+const MAX_SLOPE: f64 = 0.25;
+const MOVE_COST: usize = 10;
+const UPHILL_COST: f64 = 40.0;
+const DOWNHILL_BONUS: f64 = 12.0;
+const MIN_MOVE_COST: usize = MOVE_COST - (MAX_SLOPE * DOWNHILL_BONUS) as usize;
+
+// Événements de l'environnement (probabilités par tour)
+const REGROWTH_DELAY: u64 = 60;
+const STORM_CHANCE: f64 = 0.02;
+const STORM_RADIUS: usize = 3;
+const STORM_DURATION: u32 = 40;
+const STORM_SPEED: f64 = 0.5;
+const STORM_SENSOR_RANGE: usize = 0;
+const METEOR_CHANCE: f64 = 0.01;
+const METEOR_MINERALS_CHANCE: f64 = 0.5;
 
 // Constantes de couleurs
 const DEFAULT_COLOR: Color = Color::new(1.0, 1.0, 1.0, 1.0); // White
@@ -38,72 +159,28 @@ const ROBOT_EXPLORER_COLOR: Color = Color::new(1.0, 0.0, 0.0, 1.0); // Red
 const ROBOT_EXTRACTOR_COLOR: Color = Color::new(1.0, 0.647, 0.0, 1.0); // Orange
 const STATION_COLOR: Color = Color::new(0.0, 1.0, 1.0, 1.0); // Cyan
 const FOG_COLOR: Color = Color::new(0.0, 0.0, 0.0, 1.0); // Black
+const PANEL_COLOR: Color = Color::new(0.1, 0.1, 0.1, 1.0); // Dark gray
+const STORM_COLOR: Color = Color::new(0.76, 0.6, 0.42, 0.5); // Translucent sand
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct Node {
-    x: usize,
-    y: usize,
-    cost: usize,
-    priority: usize,
-}
-// Implémentation de la structure Node
-impl Node {
-    fn new(x: usize, y: usize, cost: usize, priority: usize) -> Self {
-        Node {
-            x,
-            y,
-            cost,
-            priority,
-        }
-    }
-}
-// Implémentation des traits Ord et PartialOrd pour la structure Node
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.priority.cmp(&self.priority)
-    }
-}
-// Implémentation du trait PartialOrd pour la structure Node
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
+// Assombrissement des cases connues mais hors de portée des capteurs
+const REMEMBERED_SHADE: f32 = 0.5;
 
-// Fonction de calcul de l'heuristique
-fn heuristic(x1: usize, y1: usize, x2: usize, y2: usize) -> usize {
-    ((x2 as isize - x1 as isize).abs() + (y1 as isize - y2 as isize).abs()) as usize
-}
+// Opacité des cartes de chaleur
+const HEATMAP_ALPHA: f32 = 0.5;
 
-fn main() -> GameResult {
-    let cb = ContextBuilder::new("CosmoBots", "Team CosmoBots")
-        .window_setup(conf::WindowSetup::default().title("CosmoBots"))
-        .window_mode(conf::WindowMode::default().dimensions(
-            (MAP_SIZE as f32 * CELL_SIZE) + 1.0,
-            (MAP_SIZE as f32 * CELL_SIZE) + 1.0,
-        ));
-
-    let mut map = Map::new();
-
-    // Place les ressources
-    map.place_resources();
-
-    // Génère les emplacements des ressources
-    let resources: Vec<(usize, usize)> = map
-        .energy
-        .iter()
-        .enumerate()
-        .flat_map(|(y, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, &val)| val)
-                .map(move |(x, _)| (x, y))
-        })
-        .collect();
-
-    // Place les obstacles en évitant les emplacements des ressources
-    map.place_obstacles(&resources);
-
-    let (ctx, event_loop) = &mut cb.build()?;
-    event::run(ctx, event_loop, &mut map)
+// Surcouches de débogage
+const OVERLAY_LINE_WIDTH: f32 = 2.0;
+const SENSOR_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.2); // Translucent white
+const OPEN_SET_COLOR: Color = Color::new(0.0, 1.0, 0.0, 0.3); // Translucent green
+const CLOSED_SET_COLOR: Color = Color::new(1.0, 0.0, 1.0, 0.2); // Translucent magenta
+const LINK_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8); // White
+
+fn main() -> ExitCode {
+    match cli::run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            err.exit_code()
+        }
+    }
 }
@@ -1,337 +1,660 @@
-use ggez::graphics;
-use ggez::{event, timer, Context, GameResult};
-use noise::{Fbm, NoiseFn, Perlin};
-use rand::Rng;
-use std::time::Duration;
-
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::backlog::Backlog;
+use crate::chunk::{Cell, Chunk, Generator, Memory, ResourceKind, CHUNK_SIZE};
+use crate::environment::Environment;
+use crate::events::{EventBus, SimEvent};
+use crate::history::History;
+use crate::metrics::Metrics;
+use crate::mission::{KnownArea, MissionOutcome, Progress};
+use crate::replay::{Playback, Replay};
+use crate::robot::RobotId;
+use crate::scenario::{RobotStats, Rules};
+use crate::station::{PlacementError, StationStrategy};
+use crate::topology::Topology;
 use crate::{RobotExplorer, RobotExtractor};
-use crate::{CELL_SIZE, ENERGY_THRESHOLD, MAP_SIZE, MINERALS_THRESHOLD, OBSTACLE_THRESHOLD};
-use crate::{
-    DEFAULT_COLOR, ENERGY_COLOR, FOG_COLOR, MINERALS_COLOR, OBSTACLE_COLOR, ROBOT_EXPLORER_COLOR,
-    ROBOT_EXTRACTOR_COLOR, SCIENCE_INTERESTS_COLOR, STATION_COLOR,
-};
+use crate::{INITIAL_CHUNK_RADIUS, MIN_MAP_SIZE};
+
+// Ressources livrées à la station
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Stockpile {
+    pub energy: usize,
+    pub minerals: usize,
+}
+
+impl Stockpile {
+    pub fn add(&mut self, kind: ResourceKind) {
+        match kind {
+            ResourceKind::Energy => self.energy += 1,
+            ResourceKind::Minerals => self.minerals += 1,
+        }
+    }
+}
+
+// Ce que les robots savent d'une case
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    // Jamais observée
+    Unknown,
+    // Observée autrefois : seul son dernier état connu est disponible
+    Remembered,
+    // Actuellement dans le champ des capteurs d'un robot
+    Visible,
+}
+
+// Largeur ou hauteur admise pour un monde, au plus `max` cases :
+// MAX_MAP_SIZE, ou MAX_FULL_MAP_SIZE pour un monde généré en entier
+pub fn check_map_size(size: usize, max: usize) -> Result<(), String> {
+    if size < MIN_MAP_SIZE {
+        Err(format!(
+            "must be at least {} cells, got {}",
+            MIN_MAP_SIZE, size
+        ))
+    } else if size > max {
+        Err(format!("must be at most {} cells, got {}", max, size))
+    } else {
+        Ok(())
+    }
+}
 
 pub struct Map {
-    pub energy: [[bool; MAP_SIZE]; MAP_SIZE],
-    pub minerals: [[bool; MAP_SIZE]; MAP_SIZE],
-    pub science_interests: [[bool; MAP_SIZE]; MAP_SIZE],
-    pub obstacles: [[bool; MAP_SIZE]; MAP_SIZE],
-    pub explored: [[bool; MAP_SIZE]; MAP_SIZE],
-    pub fog_of_war: [[bool; MAP_SIZE]; MAP_SIZE],
-    pub robot_explorer: Option<RobotExplorer>,
-    pub robot_extractor: Option<RobotExtractor>,
-    pub update_timer: Duration,
-    pub game_over: bool,
-    pub nb_consumables: usize,
-    pub resources: Vec<(usize, usize)>,
+    pub seed: u32,
+    pub width: usize,
+    pub height: usize,
+    pub topology: Topology,
+    pub chunks: HashMap<(usize, usize), Chunk>,
+    pub generator: Generator,
+    pub environment: Environment,
+    pub history: History,
+    pub tick: u64,
+    // Cases couvertes par les capteurs à la fin du dernier tick
+    pub visible: HashSet<(usize, usize)>,
+    pub stockpile: Stockpile,
+    pub backlog: Backlog,
+    pub events: EventBus,
+    pub metrics: Metrics,
+    pub rules: Rules,
+    // Tirages des robots et du placement de la station, distincts de ceux de
+    // l'environnement ; leur état fait partie des sauvegardes
+    pub rng: Pcg32,
+    // Flotte déployée à la station, vide tant qu'elle n'est pas placée
+    pub robot_explorers: Vec<RobotExplorer>,
+    pub robot_extractors: Vec<RobotExtractor>,
+    // Raison de la fin de la mission, une fois terminée
+    pub outcome: Option<MissionOutcome>,
+    pub progress: Progress,
+    // Partie en cours d'enregistrement, ou partie enregistrée en cours de lecture
+    pub recording: Option<Replay>,
+    pub playback: Option<Playback>,
+    // Consommables restants, recalculés au besoin après un changement du terrain
+    consumables: OnceCell<usize>,
+    // Zone connue des robots autour de la station, parcourue à la première
+    // demande puis tenue à jour au fil des changements de leur mémoire
+    known_area: OnceCell<KnownArea>,
 }
 
 impl Map {
-    pub fn new() -> Self {
-        let mut map = Map {
-            energy: [[false; MAP_SIZE]; MAP_SIZE],
-            minerals: [[false; MAP_SIZE]; MAP_SIZE],
-            science_interests: [[false; MAP_SIZE]; MAP_SIZE],
-            obstacles: [[false; MAP_SIZE]; MAP_SIZE],
-            explored: [[false; MAP_SIZE]; MAP_SIZE],
-            fog_of_war: [[true; MAP_SIZE]; MAP_SIZE],
-            robot_explorer: None,
-            robot_extractor: None,
-            update_timer: Duration::from_secs(1),
-            game_over: false,
-            nb_consumables: 0,
-            resources: vec![],
-        };
+    pub fn new(seed: u32, width: usize, height: usize, topology: Topology) -> Self {
+        Map {
+            seed,
+            width,
+            height,
+            topology,
+            chunks: HashMap::new(),
+            generator: Generator::new(seed, topology, width, height),
+            environment: Environment::new(seed),
+            history: History::default(),
+            tick: 0,
+            visible: HashSet::new(),
+            stockpile: Stockpile::default(),
+            backlog: Backlog::default(),
+            events: EventBus::default(),
+            metrics: Metrics::default(),
+            rules: Rules::default(),
+            rng: Pcg32::seed_from_u64(seed.wrapping_add(1) as u64),
+            robot_explorers: vec![],
+            robot_extractors: vec![],
+            outcome: None,
+            progress: Progress::default(),
+            recording: None,
+            playback: None,
+            consumables: OnceCell::new(),
+            known_area: OnceCell::new(),
+        }
+    }
 
-        if let Some((x, y)) = map.init_robot_position() {
-            map.robot_explorer = Some(RobotExplorer::new(x, y));
-            map.robot_extractor = Some(RobotExtractor::new(x, y));
-            map.fog_of_war[y][x] = false;
+    // Place la station et les robots une fois le terrain généré
+    pub fn place_station(
+        &mut self,
+        strategy: StationStrategy,
+    ) -> Result<(usize, usize), PlacementError> {
+        // Une position imposée peut se trouver hors de la zone déjà générée
+        if let StationStrategy::Fixed(x, y) = strategy {
+            if x < self.width && y < self.height {
+                self.generate_chunk(x / CHUNK_SIZE, y / CHUNK_SIZE);
+            }
         }
 
-        map
+        let mut rng = self.rng.clone();
+        let (x, y) = strategy.select(self, &mut rng)?;
+        self.rng = rng;
+
+        let fleet = self.rules.fleet;
+        self.robot_explorers = vec![RobotExplorer::new(x, y); fleet.explorers];
+        self.robot_extractors = vec![RobotExtractor::new(x, y); fleet.extractors];
+        // La zone connue se parcourt depuis la nouvelle station
+        self.known_area.take();
+        self.refresh_sensors();
+
+        Ok((x, y))
     }
 
-    pub fn init_robot_position(&self) -> Option<(usize, usize)> {
-        let mut rng = rand::thread_rng();
-        let mut attempts = 0;
-        const MAX_ATTEMPTS: usize = 100;
+    pub fn chunks_x(&self) -> usize {
+        self.width.div_ceil(CHUNK_SIZE)
+    }
 
-        loop {
-            let x = rng.gen_range(0..MAP_SIZE);
-            let y = rng.gen_range(0..MAP_SIZE);
+    pub fn chunks_y(&self) -> usize {
+        self.height.div_ceil(CHUNK_SIZE)
+    }
 
-            if !self.obstacles[y][x] && !self.energy[y][x] && !self.minerals[y][x] {
-                return Some((x, y));
+    // Génère les chunks autour du centre du monde avant de placer la station
+    pub fn generate_initial_area(&mut self) {
+        let center_x = self.chunks_x() / 2;
+        let center_y = self.chunks_y() / 2;
+
+        for chunk_y in
+            center_y.saturating_sub(INITIAL_CHUNK_RADIUS)..=center_y + INITIAL_CHUNK_RADIUS
+        {
+            for chunk_x in
+                center_x.saturating_sub(INITIAL_CHUNK_RADIUS)..=center_x + INITIAL_CHUNK_RADIUS
+            {
+                self.generate_chunk(chunk_x, chunk_y);
             }
+        }
+    }
 
-            attempts += 1;
-            if attempts >= MAX_ATTEMPTS {
-                return None;
-            }
+    pub fn generate_chunk(&mut self, chunk_x: usize, chunk_y: usize) {
+        if chunk_x >= self.chunks_x() || chunk_y >= self.chunks_y() {
+            return;
+        }
+        if !self.chunks.contains_key(&(chunk_x, chunk_y)) {
+            let chunk = self.generator.generate(chunk_x, chunk_y);
+            self.chunks.insert((chunk_x, chunk_y), chunk);
+            // Les robots ne connaissent encore aucune des nouvelles cases
+            self.consumables.take();
         }
     }
 
-    pub fn place_obstacles(&mut self, _resources: &[(usize, usize)]) {
-        let mut rng = rand::thread_rng();
-        let seed = rng.gen();
-        let fbm_obstacles = Fbm::<Perlin>::new(seed);
+    // À appeler après une modification des gisements, des obstacles ou du relief
+    // sur plusieurs cases, ou de la mémoire des robots : tout est recalculé
+    pub fn terrain_changed(&mut self) {
+        self.consumables.take();
+        self.known_area.take();
+    }
 
-        for y in 0..MAP_SIZE {
-            for x in 0..MAP_SIZE {
-                if !self
-                    .resources
-                    .iter()
-                    .any(|&(res_x, res_y)| res_x == x && res_y == y)
-                {
-                    let obstacles_noise = fbm_obstacles.get([x as f64, y as f64]);
-                    self.obstacles[y][x] = obstacles_noise > OBSTACLE_THRESHOLD;
-                }
+    // À appeler après la modification d'une seule case, qui valait `before`
+    pub fn cell_changed(&mut self, x: usize, y: usize, before: &Cell) {
+        self.consumables.take();
+        self.update_known_area(x, y, before);
+    }
+
+    fn update_known_area(&mut self, x: usize, y: usize, before: &Cell) {
+        if let Some(mut area) = self.known_area.take() {
+            if area.update(self, (x, y), before) {
+                self.known_area = area.into();
             }
         }
     }
 
-    pub fn place_resources(&mut self) {
-        let mut rng = rand::thread_rng();
-        let energy_seed = rng.gen();
-        let minerals_seed = rng.gen();
-        let fbm_energy = Fbm::<Perlin>::new(energy_seed);
-        let fbm_minerals = Fbm::<Perlin>::new(minerals_seed);
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    // Case déjà générée (None si son chunk n'a pas encore été visité)
+    pub fn cell(&self, x: usize, y: usize) -> Option<&Cell> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.chunks
+            .get(&(x / CHUNK_SIZE, y / CHUNK_SIZE))
+            .map(|chunk| chunk.cell(x % CHUNK_SIZE, y % CHUNK_SIZE))
+    }
+
+    // Case modifiable, générée à la demande
+    pub fn cell_mut(&mut self, x: usize, y: usize) -> &mut Cell {
+        self.generate_chunk(x / CHUNK_SIZE, y / CHUNK_SIZE);
+        self.chunks
+            .get_mut(&(x / CHUNK_SIZE, y / CHUNK_SIZE))
+            .expect("cell outside of the map")
+            .cell_mut(x % CHUNK_SIZE, y % CHUNK_SIZE)
+    }
 
-        for y in 0..MAP_SIZE {
-            for x in 0..MAP_SIZE {
-                let energy_noise = fbm_energy.get([x as f64, y as f64]);
-                let minerals_noise = fbm_minerals.get([x as f64, y as f64]);
-                self.energy[y][x] = energy_noise > ENERGY_THRESHOLD;
-                self.minerals[y][x] = minerals_noise > MINERALS_THRESHOLD;
+    pub fn is_obstacle(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_some_and(|cell| cell.obstacle)
+    }
 
-                if self.energy[y][x] || self.minerals[y][x] {
-                    self.resources.push((x, y));
-                }
+    // Case infranchissable : obstacle ou cœur d'une tempête
+    pub fn is_blocked(&self, x: usize, y: usize) -> bool {
+        self.is_obstacle(x, y) || self.in_storm_core(x, y)
+    }
+
+    // Chaque robot connaît la station, même pendant la mise à jour des autres
+    pub fn station(&self) -> Option<(usize, usize)> {
+        self.robot_explorers
+            .first()
+            .map(|explorer| (explorer.station_x, explorer.station_y))
+            .or_else(|| {
+                self.robot_extractors
+                    .first()
+                    .map(|extractor| (extractor.station_x, extractor.station_y))
+            })
+    }
+
+    pub fn has_energy(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_some_and(|cell| cell.energy)
+    }
+
+    pub fn has_minerals(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_some_and(|cell| cell.minerals)
+    }
+
+    pub fn is_explored(&self, x: usize, y: usize) -> bool {
+        self.cell(x, y).is_some_and(|cell| cell.explored)
+    }
+
+    pub fn is_fog(&self, x: usize, y: usize) -> bool {
+        self.visibility(x, y) == Visibility::Unknown
+    }
 
-                println!("Number of consumables: {}", self.resources.len());
+    pub fn visibility(&self, x: usize, y: usize) -> Visibility {
+        if self.visible.contains(&(x, y)) {
+            Visibility::Visible
+        } else if self.cell(x, y).is_some_and(|cell| cell.memory.is_some()) {
+            Visibility::Remembered
+        } else {
+            Visibility::Unknown
+        }
+    }
+
+    // État de la case tel que les robots le connaissent : la réalité si elle est
+    // visible, le dernier état observé sinon
+    pub fn observed(&self, x: usize, y: usize) -> Option<Memory> {
+        let cell = self.cell(x, y)?;
+        if self.visible.contains(&(x, y)) {
+            Some(cell.snapshot())
+        } else {
+            cell.memory
+        }
+    }
+
+    // Obstacle connu des robots, ou cœur de tempête
+    pub fn is_known_blocked(&self, x: usize, y: usize) -> bool {
+        self.observed(x, y).is_some_and(|memory| memory.obstacle) || self.in_storm_core(x, y)
+    }
+
+    pub fn elevation(&self, x: usize, y: usize) -> f64 {
+        self.cell(x, y).map_or(0.0, |cell| cell.elevation)
+    }
+
+    // Cases voisines selon la topologie de la carte
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.topology.neighbors(x, y, self.width, self.height)
+    }
+
+    pub fn distance(&self, from: (usize, usize), to: (usize, usize)) -> usize {
+        self.topology.distance(from, to, self.width, self.height)
+    }
+
+    // Les capteurs d'un robot dissipent le brouillard autour de lui,
+    // ce qui génère au besoin les chunks touchés
+    pub fn reveal(&mut self, x: usize, y: usize, stats: RobotStats) {
+        let tick = self.tick;
+        for (nx, ny) in self.sensor_area(x, y, stats) {
+            let cell = self.cell_mut(nx, ny);
+            let before = *cell;
+            let first = cell.observe();
+            if cell.memory != before.memory {
+                self.update_known_area(nx, ny, &before);
             }
+            if first {
+                self.history.discovered_at.insert((nx, ny), tick);
+            }
+            self.visible.insert((nx, ny));
         }
     }
 
+    // Recalcule les cases visibles depuis la position actuelle des robots
+    pub fn refresh_sensors(&mut self) {
+        self.visible.clear();
+        for id in self.robot_ids() {
+            if let Some((x, y)) = self.robot_position(id) {
+                self.reveal(x, y, self.robot_stats(id));
+            }
+        }
+    }
+
+    // Cases couvertes par les capteurs d'un robot placé en (x, y) ;
+    // les tempêtes de poussière réduisent leur portée
+    pub fn sensor_area(&self, x: usize, y: usize, stats: RobotStats) -> Vec<(usize, usize)> {
+        let range = if self.in_storm(x, y) {
+            stats.storm_sensor_range
+        } else {
+            stats.sensor_range
+        };
+        self.topology.area((x, y), range, self.width, self.height)
+    }
+
+    // Cases déjà générées, dans un ordre stable
+    pub fn generated_cells(&self) -> Vec<(usize, usize)> {
+        let mut keys: Vec<&(usize, usize)> = self.chunks.keys().collect();
+        keys.sort_by_key(|&&(chunk_x, chunk_y)| (chunk_y, chunk_x));
+
+        keys.into_iter()
+            .flat_map(|&(chunk_x, chunk_y)| {
+                (0..CHUNK_SIZE).flat_map(move |local_y| {
+                    (0..CHUNK_SIZE).map(move |local_x| {
+                        (
+                            chunk_x * CHUNK_SIZE + local_x,
+                            chunk_y * CHUNK_SIZE + local_y,
+                        )
+                    })
+                })
+            })
+            .filter(|&(x, y)| self.in_bounds(x, y))
+            .collect()
+    }
+
+    // Emplacements des ressources connues
+    pub fn resources(&self) -> Vec<(usize, usize)> {
+        self.generated_cells()
+            .into_iter()
+            .filter(|&(x, y)| self.has_energy(x, y) || self.has_minerals(x, y))
+            .collect()
+    }
+
+    pub fn mission_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    // Il reste des ticks à jouer : la mission continue et la partie
+    // rejouée, s'il y en a une, n'est pas arrivée au bout
+    pub fn running(&self) -> bool {
+        !self.mission_over() && !self.playback_finished()
+    }
+
     pub fn update_robot(&mut self) {
-        if self.game_over {
+        if !self.running() {
             return;
         }
 
-        if let Some(mut extractor) = self.robot_extractor.take() {
-            if !extractor.waiting {
-                if extractor.carrying_resource {
-                    if let Some(path) = extractor.move_towards(
-                        extractor.station_x,
-                        extractor.station_y,
-                        &self.obstacles,
-                        &self.fog_of_war,
-                    ) {
-                        if path.len() > 1 {
-                            let (next_x, next_y) = path[1];
-                            extractor.x = next_x;
-                            extractor.y = next_y;
-
-                            if extractor.x == extractor.station_x
-                                && extractor.y == extractor.station_y
-                            {
-                                extractor.carrying_resource = false;
-                                extractor.waiting = true;
-                                if let Some(mut explorer) = self.robot_explorer.as_mut() {
-                                    explorer.waiting = false;
-                                }
-                                println!("Extractor returned to the station with resource. Remaining resources: {}", self.count_consumables());
-                            }
-                        }
-                    }
-                } else if let Some((target_x, target_y)) = extractor.target_position {
-                    if let Some(path) = extractor.move_towards(
-                        target_x,
-                        target_y,
-                        &self.obstacles,
-                        &self.fog_of_war,
-                    ) {
-                        if path.len() > 1 {
-                            let (next_x, next_y) = path[1];
-                            extractor.x = next_x;
-                            extractor.y = next_y;
-
-                            if extractor.x == target_x && extractor.y == target_y {
-                                extractor.collect_resource();
-                                self.energy[target_y][target_x] = false;
-                                self.minerals[target_y][target_x] = false;
-                            }
-                        }
-                    }
+        self.replay_edits();
+        self.tick += 1;
+        self.update_environment();
+
+        let before: Vec<_> = self
+            .robot_ids()
+            .into_iter()
+            .map(|id| (id, self.robot_position(id)))
+            .collect();
+
+        for index in 0..self.robot_extractors.len() {
+            // Retiré de la flotte le temps de sa mise à jour, les autres robots restent visibles
+            let mut extractor = self.robot_extractors.remove(index);
+            self.update_extractor(&mut extractor);
+            self.robot_extractors.insert(index, extractor);
+        }
+
+        // La collecte est complète d'après ce que savent les robots : zone accessible
+        // entièrement explorée, gisements connus livrés, aucun travail en attente
+        let all_resources_collected = self.rules.victory.all_collected
+            && self.backlog.jobs.is_empty()
+            && self
+                .robot_extractors
+                .iter()
+                .all(|extractor| extractor.cargo.is_none() && extractor.target_position.is_none())
+            && self.known_deposits() == Some(0);
+
+        for index in 0..self.robot_explorers.len() {
+            let mut explorer = self.robot_explorers.remove(index);
+            self.update_explorer(&mut explorer, all_resources_collected);
+            self.robot_explorers.insert(index, explorer);
+        }
+        // La mission se termine quand tous les explorateurs sont rentrés
+        if all_resources_collected
+            && self.robot_explorers.iter().all(|explorer| {
+                (explorer.x, explorer.y) == (explorer.station_x, explorer.station_y)
+            })
+        {
+            self.end_mission(MissionOutcome::AllCollected);
+        }
+
+        self.refresh_sensors();
+
+        for &(id, position) in &before {
+            if let Some((x, y)) = self.robot_position(id) {
+                if position != Some((x, y)) {
+                    self.record_visit(id, x, y);
                 }
             }
-            self.robot_extractor = Some(extractor);
         }
+        self.record_metrics(&before);
+        self.check_end_conditions();
+    }
 
-        if let Some(mut explorer) = self.robot_explorer.take() {
-            if (!explorer.waiting) {
-                let mut rng = rand::thread_rng();
-
-                let all_resources_collected = self.count_consumables() == 0 || self.is_map_empty();
-
-                if all_resources_collected {
-                    if let Some(path) =
-                        explorer.return_to_station(&self.obstacles, &self.fog_of_war)
-                    {
-                        if path.len() > 1 {
-                            let (next_x, next_y) = path[1];
-                            explorer.x = next_x;
-                            explorer.y = next_y;
-
-                            if explorer.x == explorer.station_x && explorer.y == explorer.station_y
-                            {
-                                self.game_over = true;
-                                println!("Game Over: Robot returned to the station. All resources have been collected.");
-                            }
+    // Un tick d'extracteur : travail confié par la station, trajet, extraction ou livraison
+    fn update_extractor(&mut self, extractor: &mut RobotExtractor) {
+        // La station confie son travail le plus prioritaire à l'extracteur rentré à vide
+        if extractor.cargo.is_none()
+            && extractor.target_position.is_none()
+            && (extractor.x, extractor.y) == (extractor.station_x, extractor.station_y)
+        {
+            match self.next_job() {
+                Some(job) => {
+                    extractor.target_position = Some((job.x, job.y));
+                    extractor.waiting = false;
+                    self.emit(SimEvent::JobAssigned {
+                        kind: job.kind,
+                        x: job.x,
+                        y: job.y,
+                    });
+                }
+                None => extractor.waiting = true,
+            }
+        }
+
+        if !extractor.waiting {
+            let start = (extractor.x, extractor.y);
+            if let Some(kind) = extractor.cargo {
+                if let Some(path) =
+                    extractor.move_towards(extractor.station_x, extractor.station_y, self)
+                {
+                    if path.len() > 1 {
+                        extractor.step(path[1], self);
+
+                        if extractor.x == extractor.station_x && extractor.y == extractor.station_y
+                        {
+                            extractor.cargo = None;
+                            self.stockpile.add(kind);
+                            self.emit(SimEvent::ResourceDelivered {
+                                kind,
+                                remaining: self.count_consumables(),
+                            });
                         }
                     }
-                } else if explorer.founded_resource {
-                    if let Some((resource_x, resource_y)) = explorer.resource_position {
-                        if explorer.x == explorer.station_x && explorer.y == explorer.station_y {
-                            self.robot_extractor.as_mut().unwrap().target_position =
-                                Some((resource_x, resource_y));
-                            explorer.founded_resource = false;
-                            explorer.resource_position = None;
-                            explorer.waiting = true;
-                            if let Some(mut extractor) = self.robot_extractor.as_mut() {
-                                extractor.waiting = false;
+                }
+            } else if let Some((target_x, target_y)) = extractor.target_position {
+                // Le gisement a disparu (météore) : l'extracteur rentre chercher un
+                // autre travail. Il ne le sait qu'une fois la case revue par un robot
+                let remembered = self
+                    .observed(target_x, target_y)
+                    .is_some_and(|memory| memory.energy || memory.minerals);
+                if !remembered {
+                    extractor.target_position = None;
+                    self.requeue_remaining(target_x, target_y);
+                    self.emit(SimEvent::DepositVanished {
+                        x: target_x,
+                        y: target_y,
+                    });
+                } else if let Some(path) = extractor.move_towards(target_x, target_y, self) {
+                    if path.len() > 1 {
+                        extractor.step(path[1], self);
+
+                        if extractor.x == target_x && extractor.y == target_y {
+                            let cell = self.cell_mut(target_x, target_y);
+                            let before = *cell;
+                            let kind = cell.resource();
+                            cell.energy = false;
+                            cell.minerals = false;
+                            cell.renewed = false;
+                            self.cell_changed(target_x, target_y, &before);
+                            extractor.target_position = None;
+                            if let Some(kind) = kind {
+                                extractor.collect_resource(kind);
+                                self.emit(SimEvent::ResourceCollected {
+                                    kind,
+                                    x: target_x,
+                                    y: target_y,
+                                });
                             }
-                            println!("Explorer returned to the station and provided resource position to Extractor.");
-                        } else if let Some(path) =
-                            explorer.return_to_station(&self.obstacles, &self.fog_of_war)
-                        {
-                            if path.len() > 1 {
-                                let (next_x, next_y) = path[1];
-                                explorer.x = next_x;
-                                explorer.y = next_y;
+                            if kind == Some(ResourceKind::Energy) {
+                                self.schedule_regrowth(target_x, target_y);
                             }
+                            self.requeue_remaining(target_x, target_y);
                         }
                     }
-                } else {
-                    explorer.move_random(&mut rng, self);
+                }
+            } else if let Some(path) =
+                extractor.move_towards(extractor.station_x, extractor.station_y, self)
+            {
+                // Rien à rapporter : retour à la station pour un nouveau travail
+                if path.len() > 1 {
+                    extractor.step(path[1], self);
+                }
+            }
+            // Un robot qui devait se déplacer et n'a pas pu est bloqué
+            extractor.stuck_ticks = if (extractor.x, extractor.y) == start {
+                extractor.stuck_ticks + 1
+            } else {
+                0
+            };
+        }
+    }
+
+    // L'explorateur rentre à la station une fois la collecte complète
+    fn update_explorer(&mut self, explorer: &mut RobotExplorer, all_resources_collected: bool) {
+        let start = (explorer.x, explorer.y);
+        let at_station = start == (explorer.station_x, explorer.station_y);
+        if all_resources_collected {
+            if let Some(path) = explorer.return_to_station(self) {
+                if path.len() > 1 {
+                    explorer.step(path[1], self);
+                }
+            }
+        } else if explorer.reporting && at_station {
+            explorer.reporting = false;
+            for deposit in std::mem::take(&mut explorer.deposits) {
+                self.report_deposit(deposit);
+            }
+        } else if explorer.reporting {
+            if let Some(path) = explorer.return_to_station(self) {
+                if path.len() > 1 {
+                    explorer.step(path[1], self);
                 }
             }
-            self.robot_explorer = Some(explorer);
+        } else {
+            explorer.explore(self);
+            explorer.reporting = explorer.should_report(self);
         }
+        explorer.stuck_ticks = if (explorer.x, explorer.y) == start {
+            explorer.stuck_ticks + 1
+        } else {
+            0
+        };
+    }
+
+    // Chaque case observée a une date de découverte
+    pub fn explored_fraction(&self) -> f64 {
+        self.history.discovered_at.len() as f64 / (self.width * self.height) as f64
+    }
+
+    // Gisements connus dans la zone accessible depuis la station, None tant que
+    // la station manque ou que les robots n'ont pas exploré toute cette zone
+    pub fn known_deposits(&self) -> Option<usize> {
+        self.known_area()?.deposits()
     }
 
-    pub fn is_map_empty(&self) -> bool {
-        !self.energy.iter().any(|row| row.iter().any(|&val| val))
-            && !self.minerals.iter().any(|row| row.iter().any(|&val| val))
+    pub fn known_area(&self) -> Option<&KnownArea> {
+        let station = self.station()?;
+        Some(
+            self.known_area
+                .get_or_init(|| KnownArea::new(self, station)),
+        )
     }
 
     pub fn count_consumables(&self) -> usize {
-        self.energy
+        *self.consumables.get_or_init(|| {
+            self.generated_cells()
+                .into_iter()
+                .map(|(x, y)| self.has_energy(x, y) as usize + self.has_minerals(x, y) as usize)
+                .sum()
+        })
+    }
+
+    // Robots de la flotte, explorateurs d'abord
+    pub fn robot_ids(&self) -> Vec<RobotId> {
+        (0..self.robot_explorers.len())
+            .map(RobotId::Explorer)
+            .chain((0..self.robot_extractors.len()).map(RobotId::Extractor))
+            .collect()
+    }
+
+    // Robot suivant dans l'ordre de la flotte, en revenant au premier après le dernier
+    pub fn next_robot(&self, id: RobotId) -> RobotId {
+        let ids = self.robot_ids();
+        let next = ids
             .iter()
-            .flatten()
-            .chain(self.minerals.iter().flatten())
-            .filter(|&&val| val)
-            .count()
+            .position(|&other| other == id)
+            .map_or(0, |index| index + 1);
+        ids.get(next).or(ids.first()).copied().unwrap_or(id)
     }
-}
 
-impl event::EventHandler for Map {
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        graphics::clear(ctx, graphics::WHITE);
-
-        let explorer = self.robot_explorer.as_ref().unwrap();
-        let extractor = self.robot_extractor.as_ref().unwrap();
-
-        let explorer_at_station =
-            explorer.x == explorer.station_x && explorer.y == explorer.station_y;
-        let extractor_at_station =
-            extractor.x == extractor.station_x && extractor.y == extractor.station_y;
-
-        let all_resources_collected = self.count_consumables() == 0;
-
-        for y in 0..MAP_SIZE {
-            for x in 0..MAP_SIZE {
-                let rect = graphics::Rect::new(
-                    x as f32 * CELL_SIZE as f32,
-                    y as f32 * CELL_SIZE as f32,
-                    CELL_SIZE as f32,
-                    CELL_SIZE as f32,
-                );
-                let cell_color = if all_resources_collected
-                    && explorer.station_x == x
-                    && explorer.station_y == y
-                {
-                    STATION_COLOR
-                } else if self.obstacles[y][x] {
-                    OBSTACLE_COLOR
-                } else if self.energy[y][x] {
-                    ENERGY_COLOR
-                } else if self.minerals[y][x] {
-                    MINERALS_COLOR
-                } else if self.science_interests[y][x] {
-                    SCIENCE_INTERESTS_COLOR
-                } else {
-                    DEFAULT_COLOR
-                };
-
-                let fog_color = if self.fog_of_war[y][x] {
-                    FOG_COLOR
-                } else {
-                    cell_color
-                };
-
-                let cell = graphics::Mesh::new_rectangle(
-                    ctx,
-                    graphics::DrawMode::fill(),
-                    rect,
-                    fog_color,
-                )?;
-                graphics::draw(ctx, &cell, graphics::DrawParam::default())?;
-            }
+    pub fn robot_position(&self, id: RobotId) -> Option<(usize, usize)> {
+        match id {
+            RobotId::Explorer(index) => self
+                .robot_explorers
+                .get(index)
+                .map(|robot| (robot.x, robot.y)),
+            RobotId::Extractor(index) => self
+                .robot_extractors
+                .get(index)
+                .map(|robot| (robot.x, robot.y)),
         }
+    }
 
-        let explorer_x = explorer.x as f32 * CELL_SIZE as f32 + CELL_SIZE as f32 / 2.0;
-        let explorer_y = explorer.y as f32 * CELL_SIZE as f32 + CELL_SIZE as f32 / 2.0;
-        let explorer_circle = graphics::Mesh::new_circle(
-            ctx,
-            graphics::DrawMode::fill(),
-            [explorer_x, explorer_y],
-            CELL_SIZE as f32 / 2.0,
-            1.0,
-            ROBOT_EXPLORER_COLOR,
-        )?;
-        graphics::draw(ctx, &explorer_circle, graphics::DrawParam::default())?;
-
-        let extractor_x = extractor.x as f32 * CELL_SIZE as f32 + CELL_SIZE as f32 / 2.0;
-        let extractor_y = extractor.y as f32 * CELL_SIZE as f32 + CELL_SIZE as f32 / 2.0;
-        let extractor_circle = graphics::Mesh::new_circle(
-            ctx,
-            graphics::DrawMode::fill(),
-            [extractor_x, extractor_y],
-            CELL_SIZE as f32 / 2.0,
-            1.0,
-            ROBOT_EXTRACTOR_COLOR,
-        )?;
-        graphics::draw(ctx, &extractor_circle, graphics::DrawParam::default())?;
-
-        graphics::present(ctx)?;
-        Ok(())
+    // Premier robot de la flotte présent sur la case
+    pub fn robot_at(&self, x: usize, y: usize) -> Option<RobotId> {
+        self.robot_ids()
+            .into_iter()
+            .find(|&id| self.robot_position(id) == Some((x, y)))
     }
 
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        while timer::check_update_time(ctx, 1) {
-            if self.update_timer.as_secs() == 0 {
-                self.update_timer = Duration::from_secs(1);
-            } else {
-                self.update_timer = self
-                    .update_timer
-                    .checked_sub(Duration::from_secs(1))
-                    .unwrap_or(Duration::from_secs(0));
-            }
+    // Station et cases occupées par un robot
+    pub fn occupied_cells(&self) -> HashSet<(usize, usize)> {
+        self.robot_ids()
+            .into_iter()
+            .filter_map(|id| self.robot_position(id))
+            .chain(self.station())
+            .collect()
+    }
 
-            self.update_robot();
+    pub fn robot_stats(&self, id: RobotId) -> RobotStats {
+        match id {
+            RobotId::Explorer(_) => self.rules.explorer,
+            RobotId::Extractor(_) => self.rules.extractor,
         }
-        Ok(())
     }
 }
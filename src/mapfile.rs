@@ -0,0 +1,263 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::map::check_map_size;
+use crate::topology::Topology;
+use crate::Map;
+use crate::MAX_FULL_MAP_SIZE;
+
+const HEADER: &str = "cosmobots-map 1";
+
+// Fichier de carte : le terrain d'un monde entièrement généré, une case par caractère.
+// # obstacle, e énergie, m minerais, s intérêt scientifique, . sol nu ;
+// l'altitude suit dans une seconde grille, une valeur par case entre 0 (creux)
+// et 1 (sommet), écrite sans perte de précision
+#[derive(Debug)]
+pub enum MapFileError {
+    Io(io::Error),
+    Parse(usize, String),
+}
+
+impl fmt::Display for MapFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapFileError::Io(err) => write!(f, "cannot read map: {}", err),
+            MapFileError::Parse(line, message) => {
+                write!(f, "invalid map at line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapFileError {}
+
+impl From<io::Error> for MapFileError {
+    fn from(err: io::Error) -> Self {
+        MapFileError::Io(err)
+    }
+}
+
+fn parse<T: std::str::FromStr>(line: usize, value: Option<&str>) -> Result<T, MapFileError> {
+    let value = value.ok_or_else(|| MapFileError::Parse(line, "missing value".to_string()))?;
+    value
+        .parse()
+        .map_err(|_| MapFileError::Parse(line, format!("invalid value '{}'", value)))
+}
+
+impl Map {
+    pub fn generate_all(&mut self) {
+        for chunk_y in 0..self.chunks_y() {
+            for chunk_x in 0..self.chunks_x() {
+                self.generate_chunk(chunk_x, chunk_y);
+            }
+        }
+    }
+
+    // Écrit le terrain du monde ; les chunks manquants sont générés
+    pub fn save_terrain(&mut self, path: &Path) -> io::Result<()> {
+        self.generate_all();
+        let mut text = format!(
+            "{}\nseed {}\nsize {} {}\ntopology {}\nterrain\n",
+            HEADER, self.seed, self.width, self.height, self.topology
+        );
+        for y in 0..self.height {
+            text.extend((0..self.width).map(|x| {
+                let cell = self.cell(x, y).expect("the whole map was generated");
+                if cell.obstacle {
+                    '#'
+                } else if cell.energy {
+                    'e'
+                } else if cell.minerals {
+                    'm'
+                } else if cell.science_interests {
+                    's'
+                } else {
+                    '.'
+                }
+            }));
+            text.push('\n');
+        }
+        text.push_str("elevation\n");
+        for y in 0..self.height {
+            let row: Vec<String> = (0..self.width)
+                .map(|x| self.elevation(x, y).to_string())
+                .collect();
+            text.push_str(&row.join(" "));
+            text.push('\n');
+        }
+        fs::write(path, text)
+    }
+
+    // Monde dont le terrain vient d'un fichier ; la graine du fichier, qui règle
+    // aussi les tempêtes et les météores, peut être remplacée
+    pub fn load_terrain(path: &Path, seed: Option<u32>) -> Result<Map, MapFileError> {
+        let text = fs::read_to_string(path)?;
+        // Les lignes manquantes sont signalées juste après la fin du fichier
+        let end = text.lines().count() + 1;
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(MapFileError::Parse(1, "not a map file".to_string()));
+        }
+        let mut field = |name: &str| -> Result<(usize, Vec<&str>), MapFileError> {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| MapFileError::Parse(end, format!("missing '{}'", name)))?;
+            let mut words = line.split_whitespace();
+            if words.next() != Some(name) {
+                return Err(MapFileError::Parse(number, format!("expected '{}'", name)));
+            }
+            Ok((number, words.collect()))
+        };
+
+        let (line, file_seed) = field("seed")?;
+        let file_seed = parse(line, file_seed.first().copied())?;
+        let (line, size) = field("size")?;
+        let width: usize = parse(line, size.first().copied())?;
+        let height: usize = parse(line, size.get(1).copied())?;
+        // Le monde est généré en entier avant d'être rempli
+        for (name, value) in [("width", width), ("height", height)] {
+            check_map_size(value, MAX_FULL_MAP_SIZE)
+                .map_err(|message| MapFileError::Parse(line, format!("{} {}", name, message)))?;
+        }
+        let (line, topology) = field("topology")?;
+        let topology: Topology = parse(line, topology.first().copied())?;
+
+        let mut map = Map::new(seed.unwrap_or(file_seed), width, height, topology);
+        map.generate_all();
+
+        field("terrain")?;
+        for y in 0..height {
+            let (line, row) = field_row(&mut lines, end, "terrain")?;
+            if row.chars().count() != width {
+                return Err(MapFileError::Parse(
+                    line,
+                    format!("expected {} cells, found {}", width, row.chars().count()),
+                ));
+            }
+            for (x, symbol) in row.chars().enumerate() {
+                let cell = map.cell_mut(x, y);
+                cell.obstacle = symbol == '#';
+                cell.energy = symbol == 'e';
+                cell.minerals = symbol == 'm';
+                cell.science_interests = symbol == 's';
+                if !"#ems.".contains(symbol) {
+                    return Err(MapFileError::Parse(
+                        line,
+                        format!("unknown terrain '{}'", symbol),
+                    ));
+                }
+            }
+        }
+
+        let (line, elevation) = lines
+            .next()
+            .ok_or_else(|| MapFileError::Parse(end, "missing 'elevation'".to_string()))?;
+        if elevation != "elevation" {
+            return Err(MapFileError::Parse(
+                line,
+                "expected 'elevation'".to_string(),
+            ));
+        }
+        for y in 0..height {
+            let (line, row) = field_row(&mut lines, end, "elevation")?;
+            let values: Vec<&str> = row.split_whitespace().collect();
+            if values.len() != width {
+                return Err(MapFileError::Parse(
+                    line,
+                    format!("expected {} cells, found {}", width, values.len()),
+                ));
+            }
+            for (x, value) in values.into_iter().enumerate() {
+                let elevation: f64 = parse(line, Some(value))?;
+                if !(0.0..=1.0).contains(&elevation) {
+                    return Err(MapFileError::Parse(
+                        line,
+                        format!("elevation '{}' is not between 0 and 1", value),
+                    ));
+                }
+                map.cell_mut(x, y).elevation = elevation;
+            }
+        }
+        map.terrain_changed();
+
+        Ok(map)
+    }
+}
+
+// Ligne suivante d'une grille ; `end` est la ligne qui suit la fin du fichier
+fn field_row<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    end: usize,
+    grid: &str,
+) -> Result<(usize, &'a str), MapFileError> {
+    lines
+        .next()
+        .ok_or_else(|| MapFileError::Parse(end, format!("{} grid is too short", grid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("cosmobots-{}-{}.map", std::process::id(), name))
+    }
+
+    #[test]
+    fn saved_terrain_loads_back_unchanged() {
+        let path = temp_path("round-trip");
+        let copy = temp_path("round-trip-copy");
+        let mut map = Map::new(5, 40, 24, Topology::Hex);
+        map.save_terrain(&path).unwrap();
+
+        let mut loaded = Map::load_terrain(&path, None).unwrap();
+        assert_eq!(loaded.seed, 5);
+        assert_eq!((loaded.width, loaded.height), (40, 24));
+        assert_eq!(loaded.topology, Topology::Hex);
+        for y in 0..24 {
+            for x in 0..40 {
+                assert_eq!(loaded.elevation(x, y), map.elevation(x, y));
+            }
+        }
+        loaded.save_terrain(&copy).unwrap();
+        let (text, copied) = (fs::read_to_string(&path), fs::read_to_string(&copy));
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&copy).unwrap();
+        assert_eq!(text.unwrap(), copied.unwrap());
+    }
+
+    #[test]
+    fn truncated_file_reports_the_missing_line() {
+        let path = temp_path("truncated");
+        fs::write(
+            &path,
+            "cosmobots-map 1\nseed 1\nsize 3 3\ntopology square8\nterrain\n...\n",
+        )
+        .unwrap();
+        let result = Map::load_terrain(&path, None);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(MapFileError::Parse(7, _))));
+    }
+
+    #[test]
+    fn sizes_outside_the_limits_are_rejected() {
+        let path = temp_path("too-large");
+        fs::write(
+            &path,
+            format!(
+                "cosmobots-map 1\nseed 1\nsize 3 {}\ntopology square8\n",
+                MAX_FULL_MAP_SIZE + 1
+            ),
+        )
+        .unwrap();
+        let result = Map::load_terrain(&path, None);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(MapFileError::Parse(3, _))));
+    }
+}
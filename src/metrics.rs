@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use crate::events::SimEvent;
+use crate::mission::MissionOutcome;
+use crate::robot::RobotId;
+use crate::Map;
+use crate::METRICS_INTERVAL;
+
+// Compteurs cumulés des robots d'un même type depuis le début de la mission
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RobotMetrics {
+    // Ticks passés à attendre à la station
+    pub idle_ticks: u64,
+    // Cases parcourues
+    pub distance: u64,
+    pub battery_used: usize,
+}
+
+// Un point des séries temporelles, relevé tous les METRICS_INTERVAL ticks
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub tick: u64,
+    pub explored_fraction: f64,
+    pub deposits_discovered: usize,
+    pub units_delivered: usize,
+    pub explorer_idle_ticks: u64,
+    pub explorer_distance: u64,
+    pub explorer_battery_used: usize,
+    pub extractor_idle_ticks: u64,
+    pub extractor_distance: u64,
+    pub extractor_battery_used: usize,
+}
+
+// Bilan de la mission, pour comparer les stratégies entre elles
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Summary {
+    pub ticks: u64,
+    pub ticks_to_full_exploration: Option<u64>,
+    // Seulement pour une mission menée à son terme
+    pub ticks_to_completion: Option<u64>,
+    pub outcome: Option<MissionOutcome>,
+    pub explored_fraction: f64,
+    pub deposits_discovered: usize,
+    pub units_delivered: usize,
+    pub explorer: RobotMetrics,
+    pub extractor: RobotMetrics,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Metrics {
+    pub deposits_discovered: usize,
+    pub explorer: RobotMetrics,
+    pub extractor: RobotMetrics,
+    pub full_exploration_at: Option<u64>,
+    pub ended_at: Option<u64>,
+    pub samples: Vec<Sample>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    summary: Summary,
+    samples: &'a [Sample],
+}
+
+impl Metrics {
+    pub fn robot_mut(&mut self, id: RobotId) -> &mut RobotMetrics {
+        match id {
+            RobotId::Explorer(_) => &mut self.explorer,
+            RobotId::Extractor(_) => &mut self.extractor,
+        }
+    }
+
+    // Les découvertes et la fin de la mission sont connues par le journal des événements
+    pub fn observe(&mut self, tick: u64, event: &SimEvent) {
+        match event {
+            SimEvent::ResourceFound { .. } => self.deposits_discovered += 1,
+            SimEvent::MissionEnded { .. } => self.ended_at = Some(tick),
+            _ => (),
+        }
+    }
+}
+
+impl Map {
+    pub fn units_delivered(&self) -> usize {
+        self.stockpile.energy + self.stockpile.minerals
+    }
+
+    // Met à jour les compteurs à la fin d'un tick ; `before` donne la position
+    // de chaque robot au début du tick
+    pub fn record_metrics(&mut self, before: &[(RobotId, Option<(usize, usize)>)]) {
+        for &(id, position) in before {
+            // Un explorateur n'attend jamais : il est inactif quand il ne peut pas avancer
+            let waiting = match id {
+                RobotId::Explorer(index) => self
+                    .robot_explorers
+                    .get(index)
+                    .is_some_and(|robot| robot.stuck_ticks > 0),
+                RobotId::Extractor(index) => self
+                    .robot_extractors
+                    .get(index)
+                    .is_some_and(|robot| robot.waiting),
+            };
+            let moved = self.robot_position(id) != position;
+
+            let robot = self.metrics.robot_mut(id);
+            if waiting {
+                robot.idle_ticks += 1;
+            }
+            if moved {
+                robot.distance += 1;
+            }
+        }
+        self.metrics.explorer.battery_used = self
+            .robot_explorers
+            .iter()
+            .map(|robot| robot.energy_used)
+            .sum();
+        self.metrics.extractor.battery_used = self
+            .robot_extractors
+            .iter()
+            .map(|robot| robot.energy_used)
+            .sum();
+
+        let explored_fraction = self.explored_fraction();
+        if self.metrics.full_exploration_at.is_none() && explored_fraction >= 1.0 {
+            self.metrics.full_exploration_at = Some(self.tick);
+        }
+        if self.tick.is_multiple_of(METRICS_INTERVAL) {
+            let sample = self.sample();
+            self.metrics.samples.push(sample);
+        }
+    }
+
+    fn sample(&self) -> Sample {
+        let metrics = &self.metrics;
+        Sample {
+            tick: self.tick,
+            explored_fraction: self.explored_fraction(),
+            deposits_discovered: metrics.deposits_discovered,
+            units_delivered: self.units_delivered(),
+            explorer_idle_ticks: metrics.explorer.idle_ticks,
+            explorer_distance: metrics.explorer.distance,
+            explorer_battery_used: metrics.explorer.battery_used,
+            extractor_idle_ticks: metrics.extractor.idle_ticks,
+            extractor_distance: metrics.extractor.distance,
+            extractor_battery_used: metrics.extractor.battery_used,
+        }
+    }
+
+    pub fn summary(&self) -> Summary {
+        let metrics = &self.metrics;
+        Summary {
+            ticks: self.tick,
+            ticks_to_full_exploration: metrics.full_exploration_at,
+            ticks_to_completion: metrics
+                .ended_at
+                .filter(|_| self.outcome.is_some_and(|outcome| outcome.is_success())),
+            outcome: self.outcome,
+            explored_fraction: self.explored_fraction(),
+            deposits_discovered: metrics.deposits_discovered,
+            units_delivered: self.units_delivered(),
+            explorer: metrics.explorer,
+            extractor: metrics.extractor,
+        }
+    }
+
+    // Séries temporelles jusqu'au tick courant, même s'il ne tombe pas sur un relevé
+    fn samples(&self) -> Vec<Sample> {
+        let mut samples = self.metrics.samples.clone();
+        if samples.last().is_none_or(|sample| sample.tick != self.tick) {
+            samples.push(self.sample());
+        }
+        samples
+    }
+
+    // Un fichier .json contient le bilan et les séries temporelles,
+    // tout autre fichier reçoit les séries temporelles au format CSV
+    pub fn export_metrics(&self, path: &Path) -> io::Result<()> {
+        let samples = self.samples();
+        let writer = BufWriter::new(File::create(path)?);
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let report = Report {
+                summary: self.summary(),
+                samples: &samples,
+            };
+            serde_json::to_writer_pretty(writer, &report)?;
+        } else {
+            let mut writer = csv::Writer::from_writer(writer);
+            for sample in samples {
+                writer.serialize(sample)?;
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
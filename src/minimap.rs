@@ -0,0 +1,139 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, FilterMode, Image, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+use crate::camera::Camera;
+use crate::map::Visibility;
+use crate::robot::RobotId;
+use crate::Map;
+use crate::{
+    CELL_SIZE, DEFAULT_COLOR, ENERGY_COLOR, FOG_COLOR, MINERALS_COLOR, MINIMAP_SIZE,
+    OBSTACLE_COLOR, PANEL_COLOR, REMEMBERED_SHADE, ROBOT_EXPLORER_COLOR, ROBOT_EXTRACTOR_COLOR,
+    SCIENCE_INTERESTS_COLOR, STATION_COLOR,
+};
+
+// Résolution de la minimap : une case par pixel, au plus MINIMAP_SIZE pixels de côté
+fn resolution(map: &Map) -> (usize, usize) {
+    let limit = MINIMAP_SIZE as usize;
+    (map.width.min(limit), map.height.min(limit))
+}
+
+// Couleur d'une case telle que les robots la connaissent
+fn cell_color(map: &Map, x: usize, y: usize) -> Color {
+    if map.station() == Some((x, y)) {
+        return STATION_COLOR;
+    }
+    let Some(observed) = map.observed(x, y) else {
+        return FOG_COLOR;
+    };
+    let color = if observed.obstacle {
+        OBSTACLE_COLOR
+    } else if observed.energy {
+        ENERGY_COLOR
+    } else if observed.minerals {
+        MINERALS_COLOR
+    } else if observed.science_interests {
+        SCIENCE_INTERESTS_COLOR
+    } else {
+        DEFAULT_COLOR
+    };
+    if map.visibility(x, y) == Visibility::Remembered {
+        Color::new(
+            color.r * REMEMBERED_SHADE,
+            color.g * REMEMBERED_SHADE,
+            color.b * REMEMBERED_SHADE,
+            color.a,
+        )
+    } else {
+        color
+    }
+}
+
+// Position sur la minimap, en pixels de l'écran, d'un point de la grille
+fn to_minimap(map: &Map, area: Rect, column: f32, row: f32) -> [f32; 2] {
+    [
+        area.x + column / map.width as f32 * area.w,
+        area.y + row / map.height as f32 * area.h,
+    ]
+}
+
+// Vue d'ensemble du monde : brouillard, cases connues, robots et cadre de la caméra
+pub fn draw(ctx: &mut Context, map: &Map, camera: &Camera, view: Rect, area: Rect) -> GameResult {
+    let (columns, rows) = resolution(map);
+    let mut pixels = Vec::with_capacity(columns * rows * 4);
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = column * map.width / columns;
+            let y = row * map.height / rows;
+            let (r, g, b, a) = cell_color(map, x, y).to_rgba();
+            pixels.extend([r, g, b, a]);
+        }
+    }
+    let mut image = Image::from_rgba8(ctx, columns as u16, rows as u16, &pixels)?;
+    image.set_filter(FilterMode::Nearest);
+
+    let border = graphics::Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(area.x - 2.0, area.y - 2.0, area.w + 4.0, area.h + 4.0),
+        PANEL_COLOR,
+    )?;
+    graphics::draw(ctx, &border, DrawParam::default())?;
+    graphics::draw(
+        ctx,
+        &image,
+        DrawParam::default()
+            .dest([area.x, area.y])
+            .scale([area.w / columns as f32, area.h / rows as f32]),
+    )?;
+
+    let mut mesh = MeshBuilder::new();
+    for id in map.robot_ids() {
+        let color = match id {
+            RobotId::Explorer(_) => ROBOT_EXPLORER_COLOR,
+            RobotId::Extractor(_) => ROBOT_EXTRACTOR_COLOR,
+        };
+        if let Some((x, y)) = map.robot_position(id) {
+            let center = to_minimap(map, area, x as f32 + 0.5, y as f32 + 0.5);
+            mesh.circle(DrawMode::fill(), center, 3.0, 0.5, color);
+        }
+    }
+
+    // Cadre de la partie du monde visible par la caméra
+    let row_height = map.topology.row_height(CELL_SIZE);
+    let top_left = camera.to_world([view.x, view.y], view);
+    let bottom_right = camera.to_world([view.right(), view.bottom()], view);
+    let clamp_x = |value: f32| value.clamp(area.x, area.right());
+    let clamp_y = |value: f32| value.clamp(area.y, area.bottom());
+    let start = to_minimap(map, area, top_left[0] / CELL_SIZE, top_left[1] / row_height);
+    let end = to_minimap(
+        map,
+        area,
+        bottom_right[0] / CELL_SIZE,
+        bottom_right[1] / row_height,
+    );
+    let frame = Rect::new(
+        clamp_x(start[0]),
+        clamp_y(start[1]),
+        clamp_x(end[0]) - clamp_x(start[0]),
+        clamp_y(end[1]) - clamp_y(start[1]),
+    );
+    if frame.w > 0.0 && frame.h > 0.0 {
+        mesh.rectangle(DrawMode::stroke(1.0), frame, graphics::WHITE);
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, DrawParam::default())
+}
+
+// Point du monde, en pixels, sous un clic sur la minimap
+pub fn world_point(map: &Map, area: Rect, screen: [f32; 2]) -> Option<[f32; 2]> {
+    if !area.contains(screen) {
+        return None;
+    }
+    let x = ((screen[0] - area.x) / area.w * map.width as f32) as usize;
+    let y = ((screen[1] - area.y) / area.h * map.height as f32) as usize;
+    Some(
+        map.topology
+            .cell_center(x.min(map.width - 1), y.min(map.height - 1), CELL_SIZE),
+    )
+}
@@ -0,0 +1,409 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+use crate::chunk::{Cell, Memory};
+use crate::events::SimEvent;
+use crate::pathfinding::move_cost;
+use crate::Map;
+use crate::STRANDED_TICKS;
+
+// Raison de la fin d'une mission
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum MissionOutcome {
+    // Les robots ont exploré toute la zone accessible depuis la station, tous les
+    // gisements qu'ils y connaissent sont livrés et l'explorateur est rentré
+    AllCollected,
+    QuotaDelivered { units: usize },
+    Explored { fraction: f64 },
+    TickLimit { ticks: u64 },
+    // Plus aucun robot ne peut se déplacer
+    Stranded,
+    // Ni découverte, ni livraison, ni trajet d'extraction depuis `ticks` ticks
+    Stalled { ticks: u64 },
+}
+
+impl MissionOutcome {
+    // Mission menée à son terme, par opposition à une mission interrompue
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            MissionOutcome::AllCollected
+                | MissionOutcome::QuotaDelivered { .. }
+                | MissionOutcome::Explored { .. }
+        )
+    }
+}
+
+impl fmt::Display for MissionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissionOutcome::AllCollected => write!(
+                f,
+                "all reachable resources collected, explorer back at the station"
+            ),
+            MissionOutcome::QuotaDelivered { units } => {
+                write!(f, "{} units delivered to the station", units)
+            }
+            MissionOutcome::Explored { fraction } => {
+                write!(f, "{:.0}% of the map explored", fraction * 100.0)
+            }
+            MissionOutcome::TickLimit { ticks } => write!(f, "tick limit of {} reached", ticks),
+            MissionOutcome::Stranded => write!(f, "every robot is stranded"),
+            MissionOutcome::Stalled { ticks } => write!(f, "no progress for {} ticks", ticks),
+        }
+    }
+}
+
+// Dernier progrès de la mission, pour détecter un blocage
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub tick: u64,
+    pub discovered: usize,
+    pub delivered: usize,
+    pub extractor_distance: u64,
+}
+
+// Zone que les robots savent accessible depuis la station, sans franchir
+// d'obstacle ni de pente trop raide connus. Elle est tenue à jour case par
+// case au fil des observations plutôt que reparcourue à chaque tick ; seule
+// la mémoire des robots compte, la réalité du terrain n'est pas consultée
+#[derive(Debug, Default, PartialEq)]
+pub struct KnownArea {
+    station: (usize, usize),
+    reached: HashSet<(usize, usize)>,
+    // Cases atteintes jamais observées, qui peuvent cacher d'autres gisements
+    unobserved: HashSet<(usize, usize)>,
+    // Gisements dont les robots se souviennent dans la zone, sans ceux qui
+    // ont repoussé ou qu'un météore a apportés
+    deposits: usize,
+}
+
+fn memory(map: &Map, (x, y): (usize, usize)) -> Option<Memory> {
+    map.cell(x, y).and_then(|cell| cell.memory)
+}
+
+fn counted(cell: &Cell) -> bool {
+    cell.memory
+        .is_some_and(|memory| memory.energy || memory.minerals)
+        && !cell.renewed
+}
+
+impl KnownArea {
+    pub fn new(map: &Map, station: (usize, usize)) -> Self {
+        let mut area = KnownArea {
+            station,
+            reached: HashSet::from([station]),
+            ..Default::default()
+        };
+        match map.cell(station.0, station.1) {
+            Some(cell) if cell.memory.is_some() => {
+                area.deposits = counted(cell) as usize;
+                area.expand(map, station);
+            }
+            _ => {
+                area.unobserved.insert(station);
+            }
+        }
+        area
+    }
+
+    // None tant que la zone touche une case jamais observée
+    pub fn deposits(&self) -> Option<usize> {
+        if self.unobserved.is_empty() {
+            Some(self.deposits)
+        } else {
+            None
+        }
+    }
+
+    // Parcours en largeur depuis une case observée de la zone
+    fn expand(&mut self, map: &Map, start: (usize, usize)) {
+        let mut queue = VecDeque::from([start]);
+        while let Some((x, y)) = queue.pop_front() {
+            for next in map.neighbors(x, y) {
+                if self.reached.contains(&next) {
+                    continue;
+                }
+                match map.cell(next.0, next.1) {
+                    Some(cell) if cell.memory.is_some() => {
+                        if cell.memory.is_some_and(|memory| memory.obstacle)
+                            || move_cost(map, (x, y), next).is_none()
+                        {
+                            continue;
+                        }
+                        self.deposits += counted(cell) as usize;
+                        queue.push_back(next);
+                    }
+                    // Une case inconnue est atteinte quelle que soit sa pente
+                    _ => {
+                        self.unobserved.insert(next);
+                    }
+                }
+                self.reached.insert(next);
+            }
+        }
+    }
+
+    // Case de la zone d'où le parcours est reparti vers ses voisines
+    fn expanded(&self, map: &Map, cell: (usize, usize)) -> bool {
+        self.reached.contains(&cell) && (cell == self.station || memory(map, cell).is_some())
+    }
+
+    // Prend en compte un changement de la mémoire des robots ou de l'état
+    // renouvelé d'une case, qui valait `before`. false quand la zone a pu
+    // rétrécir ou s'ouvrir ailleurs et doit être reparcourue
+    pub fn update(&mut self, map: &Map, cell: (usize, usize), before: &Cell) -> bool {
+        let Some(after) = map.cell(cell.0, cell.1) else {
+            return false;
+        };
+        if !self.reached.contains(&cell) {
+            // Un obstacle oublié peut ouvrir un passage vers d'autres cases
+            let opened = before.memory.is_some_and(|memory| memory.obstacle)
+                && after.memory.is_some_and(|memory| !memory.obstacle);
+            return !opened;
+        }
+        match (before.memory, after.memory) {
+            (None, Some(memory)) => {
+                self.unobserved.remove(&cell);
+                // Atteinte sans connaître sa pente : elle reste dans la zone si
+                // un voisin observé de la zone y mène
+                let linked = cell == self.station
+                    || (!memory.obstacle
+                        && map.neighbors(cell.0, cell.1).into_iter().any(|from| {
+                            self.expanded(map, from) && move_cost(map, from, cell).is_some()
+                        }));
+                if linked {
+                    self.deposits += counted(after) as usize;
+                    self.expand(map, cell);
+                } else {
+                    self.reached.remove(&cell);
+                }
+                true
+            }
+            (Some(old), Some(new)) => {
+                if new.obstacle && !old.obstacle && cell != self.station {
+                    return false;
+                }
+                self.deposits = self.deposits + counted(after) as usize - counted(before) as usize;
+                true
+            }
+            // Seul l'état renouvelé d'une case inconnue a changé
+            (None, None) => true,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+impl Map {
+    pub fn end_mission(&mut self, outcome: MissionOutcome) {
+        if self.outcome.is_none() {
+            self.outcome = Some(outcome);
+            self.emit(SimEvent::MissionEnded { outcome });
+        }
+    }
+
+    // Les explorateurs sont bloqués depuis STRANDED_TICKS ticks (assez pour qu'une
+    // tempête soit passée) et les extracteurs le sont aussi, ou attendent du travail
+    fn robots_stranded(&self) -> bool {
+        let explorers = self
+            .robot_explorers
+            .iter()
+            .all(|explorer| explorer.stuck_ticks >= STRANDED_TICKS);
+        let extractors = self.robot_extractors.iter().all(|extractor| {
+            extractor.stuck_ticks >= STRANDED_TICKS
+                || (extractor.waiting && extractor.cargo.is_none())
+        });
+        explorers && extractors
+    }
+
+    // Conditions de fin vérifiées à la fin de chaque tick ; la collecte
+    // complète est constatée au retour des explorateurs à la station
+    pub fn check_end_conditions(&mut self) {
+        let progress = Progress {
+            tick: self.progress.tick,
+            discovered: self.history.discovered_at.len(),
+            delivered: self.units_delivered(),
+            extractor_distance: self.metrics.extractor.distance,
+        };
+        if progress != self.progress {
+            self.progress = Progress {
+                tick: self.tick,
+                ..progress
+            };
+        }
+
+        let rules = self.rules;
+        if let Some(units) = rules
+            .victory
+            .deliveries
+            .filter(|&units| self.units_delivered() >= units)
+        {
+            self.end_mission(MissionOutcome::QuotaDelivered { units });
+        } else if let Some(fraction) = rules
+            .victory
+            .explored_fraction
+            .filter(|&fraction| self.explored_fraction() >= fraction)
+        {
+            self.end_mission(MissionOutcome::Explored { fraction });
+        } else if rules.stranded && self.robots_stranded() {
+            self.end_mission(MissionOutcome::Stranded);
+        } else if rules.stall_ticks > 0
+            && self.tick.saturating_sub(self.progress.tick) >= rules.stall_ticks
+        {
+            self.end_mission(MissionOutcome::Stalled {
+                ticks: rules.stall_ticks,
+            });
+        } else if let Some(ticks) = rules.max_ticks.filter(|&ticks| self.tick >= ticks) {
+            self.end_mission(MissionOutcome::TickLimit { ticks });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::Brush;
+    use crate::scenario::Fleet;
+    use crate::station::StationStrategy;
+    use crate::testing;
+
+    // La station de ce monde n'atteint qu'un gisement, que l'extracteur
+    // charge dès les premiers ticks
+    #[test]
+    fn all_collected_waits_for_the_extractor() {
+        let mut map = testing::mission(7, 10, StationStrategy::ResourceCentroid);
+        while !map.mission_over() && map.tick < 1000 {
+            map.update_robot();
+        }
+        assert_eq!(map.outcome, Some(MissionOutcome::AllCollected));
+        assert!(map.units_delivered() > 0);
+        let extractor = &map.robot_extractors[0];
+        assert_eq!((extractor.cargo, extractor.target_position), (None, None));
+        assert!(map.backlog.jobs.is_empty());
+    }
+
+    // Tous les explorateurs rentrent et tous les extracteurs finissent leur travail
+    #[test]
+    fn fleet_completes_the_mission() {
+        let fleet = Fleet {
+            explorers: 2,
+            extractors: 3,
+        };
+        let mut map = testing::fleet_mission(7, 32, StationStrategy::ResourceCentroid, fleet);
+        while !map.mission_over() && map.tick < 5000 {
+            map.update_robot();
+        }
+        assert_eq!(map.outcome, Some(MissionOutcome::AllCollected));
+        let station = map.station().unwrap();
+        assert!(map
+            .robot_explorers
+            .iter()
+            .all(|explorer| (explorer.x, explorer.y) == station));
+        assert!(map
+            .robot_extractors
+            .iter()
+            .all(|extractor| extractor.cargo.is_none() && extractor.target_position.is_none()));
+        assert!(map.metrics.extractor.distance > 0);
+    }
+
+    #[test]
+    fn unexplored_cells_delay_completion() {
+        let mut map = testing::mission(7, 10, StationStrategy::ResourceCentroid);
+        assert_eq!(map.known_deposits(), None);
+        testing::observe_all(&mut map);
+        assert!(map.known_deposits().is_some());
+    }
+
+    // Seule la mémoire des robots compte : un gisement disparu reste attendu
+    // et un gisement apparu reste ignoré tant qu'ils ne les ont pas revus
+    #[test]
+    fn completion_follows_robot_memory() {
+        let mut map = testing::mission(7, 10, StationStrategy::ResourceCentroid);
+        testing::observe_all(&mut map);
+        // Les robots ne voient plus rien : ils s'en tiennent à leurs souvenirs
+        map.visible.clear();
+        let station = map.station().unwrap();
+        let before = map.known_deposits().unwrap();
+        let (x, y) = map
+            .neighbors(station.0, station.1)
+            .into_iter()
+            .find(|&(x, y)| {
+                let cell = map.cell(x, y).unwrap();
+                !cell.obstacle && !cell.has_resource() && move_cost(&map, station, (x, y)).is_some()
+            })
+            .unwrap();
+
+        map.cell_mut(x, y).energy = true;
+        map.terrain_changed();
+        assert_eq!(map.known_deposits(), Some(before));
+        testing::observe_all(&mut map);
+        assert_eq!(map.known_deposits(), Some(before + 1));
+
+        map.cell_mut(x, y).energy = false;
+        map.terrain_changed();
+        assert_eq!(map.known_deposits(), Some(before + 1));
+        testing::observe_all(&mut map);
+        assert_eq!(map.known_deposits(), Some(before));
+    }
+
+    #[test]
+    fn renewed_resources_are_not_awaited() {
+        let mut map = testing::mission(7, 10, StationStrategy::ResourceCentroid);
+        testing::observe_all(&mut map);
+        let station = map.station().unwrap();
+        let before = map.known_deposits().unwrap();
+        let (x, y) = map
+            .neighbors(station.0, station.1)
+            .into_iter()
+            .find(|&(x, y)| {
+                let cell = map.cell(x, y).unwrap();
+                !cell.obstacle && !cell.has_resource() && move_cost(&map, station, (x, y)).is_some()
+            })
+            .unwrap();
+
+        map.cell_mut(x, y).energy = true;
+        testing::observe_all(&mut map);
+        assert_eq!(map.known_deposits(), Some(before + 1));
+        map.cell_mut(x, y).renewed = true;
+        map.terrain_changed();
+        assert_eq!(map.known_deposits(), Some(before));
+    }
+
+    // La zone tenue à jour au fil des observations, de l'extraction, des
+    // repousses et des retouches reste celle qu'un parcours complet trouve
+    #[test]
+    fn known_area_follows_every_change() {
+        let fleet = Fleet {
+            explorers: 2,
+            extractors: 2,
+        };
+        let mut map = testing::fleet_mission(3, 32, StationStrategy::ResourceCentroid, fleet);
+        let station = map.station().unwrap();
+        while !map.mission_over() && map.tick < 3000 {
+            map.update_robot();
+            if map.tick.is_multiple_of(100) {
+                // Une case vue par les robots reçoit un gisement
+                let occupied = map.occupied_cells();
+                let mut visible: Vec<(usize, usize)> = map.visible.iter().copied().collect();
+                visible.sort_unstable();
+                if let Some(&(x, y)) = visible
+                    .iter()
+                    .find(|&&(x, y)| !occupied.contains(&(x, y)) && !map.is_obstacle(x, y))
+                {
+                    map.paint(x, y, Brush::Energy);
+                }
+            }
+            if map.tick.is_multiple_of(20) {
+                assert_eq!(
+                    map.known_area(),
+                    Some(&KnownArea::new(&map, station)),
+                    "tick {}",
+                    map.tick
+                );
+            }
+        }
+        assert!(map.known_deposits().is_some());
+    }
+}
@@ -0,0 +1,144 @@
+use ggez::graphics::{Color, DrawMode, MeshBuilder};
+use ggez::GameResult;
+
+use crate::pathfinding::SearchTrace;
+use crate::scenario::RobotStats;
+use crate::Map;
+use crate::{
+    CELL_SIZE, CLOSED_SET_COLOR, LINK_COLOR, OPEN_SET_COLOR, OVERLAY_LINE_WIDTH,
+    ROBOT_EXPLORER_COLOR, ROBOT_EXTRACTOR_COLOR, SENSOR_COLOR,
+};
+
+// Surcouches de débogage activables séparément
+#[derive(Default)]
+pub struct Overlays {
+    pub paths: bool,
+    pub targets: bool,
+    pub sensors: bool,
+    pub search: bool,
+    pub links: bool,
+}
+
+// Ce que les surcouches montrent d'un robot
+struct RobotView<'a> {
+    position: (usize, usize),
+    path: &'a [(usize, usize)],
+    search: &'a SearchTrace,
+    target: Option<(usize, usize)>,
+    stats: RobotStats,
+    color: Color,
+}
+
+impl Overlays {
+    // Ajoute les surcouches actives au maillage ; renvoie false s'il est resté vide
+    pub fn build(&self, map: &Map, mesh: &mut MeshBuilder) -> GameResult<bool> {
+        let mut robots = vec![];
+        for explorer in &map.robot_explorers {
+            robots.push(RobotView {
+                position: (explorer.x, explorer.y),
+                path: &explorer.path,
+                search: &explorer.search,
+                target: explorer.target(),
+                stats: map.rules.explorer,
+                color: ROBOT_EXPLORER_COLOR,
+            });
+        }
+        for extractor in &map.robot_extractors {
+            robots.push(RobotView {
+                position: (extractor.x, extractor.y),
+                path: &extractor.path,
+                search: &extractor.search,
+                target: extractor.target(),
+                stats: map.rules.extractor,
+                color: ROBOT_EXTRACTOR_COLOR,
+            });
+        }
+
+        let center = |(x, y): (usize, usize)| map.topology.cell_center(x, y, CELL_SIZE);
+        let outline = |cell: (usize, usize)| map.topology.cell_outline(center(cell), CELL_SIZE);
+        let mut drawn = false;
+
+        for robot in &robots {
+            if self.search {
+                for &cell in &robot.search.closed {
+                    mesh.polygon(DrawMode::fill(), &outline(cell), CLOSED_SET_COLOR)?;
+                    drawn = true;
+                }
+                for &cell in &robot.search.open {
+                    mesh.polygon(DrawMode::fill(), &outline(cell), OPEN_SET_COLOR)?;
+                    drawn = true;
+                }
+            }
+
+            if self.sensors {
+                let (x, y) = robot.position;
+                for cell in map.sensor_area(x, y, robot.stats) {
+                    mesh.polygon(DrawMode::fill(), &outline(cell), SENSOR_COLOR)?;
+                    drawn = true;
+                }
+            }
+
+            if self.paths {
+                // Seule la partie du chemin qui reste à parcourir est tracée
+                let remaining = match robot.path.iter().position(|&cell| cell == robot.position) {
+                    Some(index) => &robot.path[index..],
+                    None => robot.path,
+                };
+                if remaining.len() > 1 {
+                    let points: Vec<[f32; 2]> =
+                        remaining.iter().map(|&cell| center(cell)).collect();
+                    mesh.line(&points, OVERLAY_LINE_WIDTH, robot.color)?;
+                    drawn = true;
+                }
+            }
+
+            if self.targets {
+                if let Some(target) = robot.target {
+                    mesh.polygon(
+                        DrawMode::stroke(OVERLAY_LINE_WIDTH),
+                        &outline(target),
+                        robot.color,
+                    )?;
+                    drawn = true;
+                }
+            }
+        }
+
+        if self.links {
+            drawn |= self.build_links(map, mesh)?;
+        }
+
+        Ok(drawn)
+    }
+
+    // Relie les gisements repérés par chaque explorateur à l'explorateur qui les
+    // rapporte, et le gisement exploité à son extracteur
+    fn build_links(&self, map: &Map, mesh: &mut MeshBuilder) -> GameResult<bool> {
+        let center = |(x, y): (usize, usize)| map.topology.cell_center(x, y, CELL_SIZE);
+        let mut drawn = false;
+
+        for explorer in &map.robot_explorers {
+            for deposit in &explorer.deposits {
+                let points = [
+                    center((explorer.x, explorer.y)),
+                    center((deposit.x, deposit.y)),
+                ];
+                if points[0] != points[1] {
+                    mesh.line(&points, OVERLAY_LINE_WIDTH, LINK_COLOR)?;
+                    drawn = true;
+                }
+            }
+        }
+        for extractor in &map.robot_extractors {
+            if let (Some(target), false) = (extractor.target_position, extractor.waiting) {
+                let points = [center(target), center((extractor.x, extractor.y))];
+                if points[0] != points[1] {
+                    mesh.line(&points, OVERLAY_LINE_WIDTH, LINK_COLOR)?;
+                    drawn = true;
+                }
+            }
+        }
+
+        Ok(drawn)
+    }
+}
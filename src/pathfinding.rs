@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::Map;
+use crate::{DOWNHILL_BONUS, MAX_SLOPE, MIN_MOVE_COST, MOVE_COST, UPHILL_COST};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Node {
+    x: usize,
+    y: usize,
+    cost: usize,
+    priority: usize,
+}
+// Implémentation de la structure Node
+impl Node {
+    fn new(x: usize, y: usize, cost: usize, priority: usize) -> Self {
+        Node {
+            x,
+            y,
+            cost,
+            priority,
+        }
+    }
+}
+// Implémentation des traits Ord et PartialOrd pour la structure Node
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+// Implémentation du trait PartialOrd pour la structure Node
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Cases ouvertes et fermées par une recherche, conservées pour l'affichage de débogage
+#[derive(Clone, Default)]
+pub struct SearchTrace {
+    pub open: HashSet<(usize, usize)>,
+    pub closed: HashSet<(usize, usize)>,
+}
+
+// Fonction de calcul de l'heuristique, adaptée à la topologie de la carte
+pub fn heuristic(map: &Map, from: (usize, usize), to: (usize, usize)) -> usize {
+    map.distance(from, to) * MIN_MOVE_COST
+}
+
+// Coût d'un déplacement entre deux cases voisines selon la pente
+// (None si la pente est trop raide)
+pub fn move_cost(
+    map: &Map,
+    (from_x, from_y): (usize, usize),
+    (to_x, to_y): (usize, usize),
+) -> Option<usize> {
+    let slope = map.elevation(to_x, to_y) - map.elevation(from_x, from_y);
+    if slope.abs() > MAX_SLOPE {
+        None
+    } else if slope > 0.0 {
+        Some(MOVE_COST + (slope * UPHILL_COST).round() as usize)
+    } else {
+        Some(MOVE_COST - (-slope * DOWNHILL_BONUS).round() as usize)
+    }
+}
+
+// Recherche A* sur les cases connues de la carte ; les tables de scores sont
+// indexées par position pour pouvoir traverser plusieurs chunks, et les cases
+// ouvertes et fermées sont notées dans `trace`
+pub fn find_path(
+    map: &Map,
+    start: (usize, usize),
+    goal: (usize, usize),
+    trace: &mut SearchTrace,
+) -> Option<Vec<(usize, usize)>> {
+    trace.open.clear();
+    trace.closed.clear();
+    trace.open.insert(start);
+
+    let mut open_list = BinaryHeap::new();
+    let mut came_from = HashMap::new();
+    let mut g_score: HashMap<(usize, usize), usize> = HashMap::new();
+
+    g_score.insert(start, 0);
+    open_list.push(Node::new(start.0, start.1, 0, heuristic(map, start, goal)));
+
+    while let Some(current) = open_list.pop() {
+        if current.x == goal.0 && current.y == goal.1 {
+            let mut path = vec![];
+            let mut current_pos = (current.x, current.y);
+            while let Some(&prev) = came_from.get(&current_pos) {
+                path.push(current_pos);
+                current_pos = prev;
+            }
+            path.push(start);
+            path.reverse();
+            return Some(path);
+        }
+
+        // Entrée obsolète de la file
+        if current.cost > g_score[&(current.x, current.y)] {
+            continue;
+        }
+        trace.open.remove(&(current.x, current.y));
+        trace.closed.insert((current.x, current.y));
+
+        for (neighbor_x, neighbor_y) in map.neighbors(current.x, current.y) {
+            // Les robots planifient d'après ce qu'ils savent de la carte
+            if map.is_known_blocked(neighbor_x, neighbor_y) || map.is_fog(neighbor_x, neighbor_y) {
+                continue;
+            }
+            let cost = match move_cost(map, (current.x, current.y), (neighbor_x, neighbor_y)) {
+                Some(cost) => cost,
+                None => continue,
+            };
+            let tentative_g_score = current.cost + cost;
+            let neighbor_g_score = g_score
+                .get(&(neighbor_x, neighbor_y))
+                .copied()
+                .unwrap_or(usize::MAX);
+            if tentative_g_score < neighbor_g_score {
+                came_from.insert((neighbor_x, neighbor_y), (current.x, current.y));
+                g_score.insert((neighbor_x, neighbor_y), tentative_g_score);
+                trace.open.insert((neighbor_x, neighbor_y));
+                open_list.push(Node::new(
+                    neighbor_x,
+                    neighbor_y,
+                    tentative_g_score,
+                    tentative_g_score + heuristic(map, (neighbor_x, neighbor_y), goal),
+                ));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology::Topology;
+
+    // Deux cases voisines d'élévations données
+    fn slope(from: f64, to: f64) -> Option<usize> {
+        let mut map = Map::new(1, 16, 16, Topology::Square4);
+        map.cell_mut(0, 0).elevation = from;
+        map.cell_mut(1, 0).elevation = to;
+        move_cost(&map, (0, 0), (1, 0))
+    }
+
+    #[test]
+    fn flat_ground_costs_the_base_move() {
+        assert_eq!(slope(0.5, 0.5), Some(MOVE_COST));
+    }
+
+    #[test]
+    fn max_slope_is_still_passable() {
+        assert_eq!(
+            slope(0.5, 0.5 + MAX_SLOPE),
+            Some(MOVE_COST + (MAX_SLOPE * UPHILL_COST).round() as usize)
+        );
+        assert_eq!(slope(0.5, 0.5 - MAX_SLOPE), Some(MIN_MOVE_COST));
+        assert_eq!(slope(0.5, 0.5 + MAX_SLOPE + 0.01), None);
+        assert_eq!(slope(0.5, 0.5 - MAX_SLOPE - 0.01), None);
+    }
+
+    #[test]
+    fn downhill_never_goes_below_the_heuristic_cost() {
+        for step in 0..=25 {
+            let drop = MAX_SLOPE * step as f64 / 25.0;
+            assert!(slope(0.5, 0.5 - drop).unwrap() >= MIN_MOVE_COST);
+        }
+    }
+}
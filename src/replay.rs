@@ -0,0 +1,613 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::chunk::Thresholds;
+use crate::editor::Brush;
+use crate::map::check_map_size;
+use crate::scenario::{
+    check_fleet_size, check_max_ticks, check_sensor_range, check_threshold, check_victory, Fleet,
+    RobotStats, Rules, Victory,
+};
+use crate::station::{PlacementError, StationStrategy};
+use crate::topology::Topology;
+use crate::Map;
+use crate::MAX_MAP_SIZE;
+
+const HEADER: &str = "cosmobots-replay 1";
+
+// Ce qui ne se déduit pas de la graine : les tirages de l'explorateur et les
+// modifications faites par l'utilisateur. Tout le reste de la simulation est
+// rejoué à l'identique à partir de la graine.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    // Indice du déplacement choisi parmi les déplacements possibles
+    ExplorerMove(usize),
+    Paint(usize, usize, Brush),
+}
+
+// Partie enregistrée : paramètres du monde et actions, chacune avec son tick
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u32,
+    pub width: usize,
+    pub height: usize,
+    pub topology: Topology,
+    pub station: (usize, usize),
+    pub thresholds: Thresholds,
+    pub rules: Rules,
+    pub actions: Vec<(u64, Action)>,
+    // Dernier tick enregistré
+    pub length: u64,
+}
+
+// Lecture en cours d'une partie enregistrée
+pub struct Playback {
+    pub replay: Arc<Replay>,
+    cursor: usize,
+    // Tick où la partie rejouée a cessé de correspondre à l'enregistrement
+    pub diverged_at: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(usize, String),
+    Diverged(u64),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "cannot read replay: {}", err),
+            ReplayError::Parse(line, message) => {
+                write!(f, "invalid replay at line {}: {}", line, message)
+            }
+            ReplayError::Diverged(tick) => write!(
+                f,
+                "replay no longer matches the recorded mission at tick {}",
+                tick
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+fn parse<T: std::str::FromStr>(line: usize, value: Option<&str>) -> Result<T, ReplayError> {
+    let value = value.ok_or_else(|| ReplayError::Parse(line, "missing value".to_string()))?;
+    value
+        .parse()
+        .map_err(|_| ReplayError::Parse(line, format!("invalid value '{}'", value)))
+}
+
+// Valeur facultative, notée « - » quand elle est absente
+fn parse_option<T: std::str::FromStr>(
+    line: usize,
+    value: Option<&str>,
+) -> Result<Option<T>, ReplayError> {
+    match value {
+        Some("-") => Ok(None),
+        value => parse(line, value).map(Some),
+    }
+}
+
+fn format_option<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+impl Replay {
+    // Commence l'enregistrement d'une partie dont la station vient d'être placée
+    pub fn new(map: &Map) -> Self {
+        Replay {
+            seed: map.seed,
+            width: map.width,
+            height: map.height,
+            topology: map.topology,
+            station: map.station().unwrap_or((0, 0)),
+            thresholds: map.generator.thresholds,
+            rules: map.rules,
+            actions: vec![],
+            length: 0,
+        }
+    }
+
+    // Format texte : un en-tête de quelques lignes puis une action par ligne
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let (thresholds, rules) = (self.thresholds, self.rules);
+        let mut text = format!(
+            "{}\nseed {}\nsize {} {}\ntopology {}\nstation {} {}\n",
+            HEADER,
+            self.seed,
+            self.width,
+            self.height,
+            self.topology,
+            self.station.0,
+            self.station.1,
+        );
+        text.push_str(&format!(
+            "thresholds {} {} {}\nfleet {} {}\nexplorer {} {}\nextractor {} {}\nstrategy {}\n",
+            thresholds.obstacle,
+            thresholds.energy,
+            thresholds.minerals,
+            rules.fleet.explorers,
+            rules.fleet.extractors,
+            rules.explorer.sensor_range,
+            rules.explorer.storm_sensor_range,
+            rules.extractor.sensor_range,
+            rules.extractor.storm_sensor_range,
+            rules.explorer_strategy,
+        ));
+        text.push_str(&format!(
+            "limits {} {} {}\nvictory {} {} {}\nlength {}\n",
+            format_option(rules.max_ticks),
+            rules.stall_ticks,
+            rules.stranded,
+            rules.victory.all_collected,
+            format_option(rules.victory.deliveries),
+            format_option(rules.victory.explored_fraction),
+            self.length
+        ));
+        for (tick, action) in &self.actions {
+            let line = match action {
+                Action::ExplorerMove(index) => format!("{} m {}\n", tick, index),
+                Action::Paint(x, y, brush) => format!("{} p {} {} {}\n", tick, x, y, brush),
+            };
+            text.push_str(&line);
+        }
+        fs::write(path, text)
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, ReplayError> {
+        let text = fs::read_to_string(path)?;
+        // Les lignes manquantes sont signalées juste après la fin du fichier
+        let end = text.lines().count() + 1;
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(ReplayError::Parse(1, "not a replay file".to_string()));
+        }
+        let mut field = |name: &str| -> Result<(usize, Vec<&str>), ReplayError> {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| ReplayError::Parse(end, format!("missing '{}'", name)))?;
+            let mut words = line.split_whitespace();
+            if words.next() != Some(name) {
+                return Err(ReplayError::Parse(number, format!("expected '{}'", name)));
+            }
+            Ok((number, words.collect()))
+        };
+
+        let (line, seed) = field("seed")?;
+        let seed = parse(line, seed.first().copied())?;
+        let (line, size) = field("size")?;
+        let (width, height) = (
+            parse(line, size.first().copied())?,
+            parse(line, size.get(1).copied())?,
+        );
+        // Mêmes bornes que pour un scénario
+        for (name, size) in [("width", width), ("height", height)] {
+            check_map_size(size, MAX_MAP_SIZE)
+                .map_err(|message| ReplayError::Parse(line, format!("{} {}", name, message)))?;
+        }
+        let (line, topology) = field("topology")?;
+        let topology = parse(line, topology.first().copied())?;
+        let (station_line, station) = field("station")?;
+        let station = (
+            parse(station_line, station.first().copied())?,
+            parse(station_line, station.get(1).copied())?,
+        );
+        let (line, values) = field("thresholds")?;
+        let thresholds = Thresholds {
+            obstacle: parse(line, values.first().copied())?,
+            energy: parse(line, values.get(1).copied())?,
+            minerals: parse(line, values.get(2).copied())?,
+        };
+        for threshold in [thresholds.obstacle, thresholds.energy, thresholds.minerals] {
+            check_threshold(threshold).map_err(|message| ReplayError::Parse(line, message))?;
+        }
+        let (line, fleet) = field("fleet")?;
+        let fleet = Fleet {
+            explorers: parse(line, fleet.first().copied())?,
+            extractors: parse(line, fleet.get(1).copied())?,
+        };
+        for count in [fleet.explorers, fleet.extractors] {
+            check_fleet_size(count).map_err(|message| ReplayError::Parse(line, message))?;
+        }
+        let mut stats = |name: &str| -> Result<RobotStats, ReplayError> {
+            let (line, values) = field(name)?;
+            let stats = RobotStats {
+                sensor_range: parse(line, values.first().copied())?,
+                storm_sensor_range: parse(line, values.get(1).copied())?,
+            };
+            check_sensor_range(stats.sensor_range)
+                .map_err(|message| ReplayError::Parse(line, format!("sensor range {}", message)))?;
+            if stats.storm_sensor_range > stats.sensor_range {
+                return Err(ReplayError::Parse(
+                    line,
+                    "storm sensor range must not exceed sensor range".to_string(),
+                ));
+            }
+            Ok(stats)
+        };
+        let (explorer, extractor) = (stats("explorer")?, stats("extractor")?);
+        let (line, strategy) = field("strategy")?;
+        let explorer_strategy = parse(line, strategy.first().copied())?;
+        let (line, limits) = field("limits")?;
+        let (max_ticks, stall_ticks, stranded) = (
+            parse_option(line, limits.first().copied())?,
+            parse(line, limits.get(1).copied())?,
+            parse(line, limits.get(2).copied())?,
+        );
+        check_max_ticks(max_ticks)
+            .map_err(|message| ReplayError::Parse(line, format!("max ticks {}", message)))?;
+        let (line, victory) = field("victory")?;
+        let victory = Victory {
+            all_collected: parse(line, victory.first().copied())?,
+            deliveries: parse_option(line, victory.get(1).copied())?,
+            explored_fraction: parse_option(line, victory.get(2).copied())?,
+        };
+        check_victory(victory)
+            .map_err(|(key, message)| ReplayError::Parse(line, format!("{} {}", key, message)))?;
+        let rules = Rules {
+            fleet,
+            explorer,
+            extractor,
+            explorer_strategy,
+            max_ticks,
+            stall_ticks,
+            stranded,
+            victory,
+        };
+        let (line, length) = field("length")?;
+        let length = parse(line, length.first().copied())?;
+
+        let mut actions = vec![];
+        for (line, text) in lines {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            let tick = parse(line, words.first().copied())?;
+            let action = match words.get(1).copied() {
+                Some("m") => Action::ExplorerMove(parse(line, words.get(2).copied())?),
+                Some("p") => Action::Paint(
+                    parse(line, words.get(2).copied())?,
+                    parse(line, words.get(3).copied())?,
+                    parse(line, words.get(4).copied())?,
+                ),
+                _ => return Err(ReplayError::Parse(line, "unknown action".to_string())),
+            };
+            actions.push((tick, action));
+        }
+
+        let replay = Replay {
+            seed,
+            width,
+            height,
+            topology,
+            station,
+            thresholds,
+            rules,
+            actions,
+            length,
+        };
+        // La station doit pouvoir être placée dans le monde régénéré
+        replay
+            .world()
+            .map_err(|err| ReplayError::Parse(station_line, err.to_string()))?;
+        Ok(replay)
+    }
+
+    // Monde au tick 0 régénéré depuis la graine, avec sa station
+    fn world(&self) -> Result<Map, PlacementError> {
+        let mut map = Map::new(self.seed, self.width, self.height, self.topology);
+        map.generator.thresholds = self.thresholds;
+        map.rules = self.rules;
+        map.generate_initial_area();
+        map.place_station(StationStrategy::Fixed(self.station.0, self.station.1))?;
+        Ok(map)
+    }
+
+    // Monde au tick 0, prêt à rejouer les actions enregistrées
+    pub fn start(self: &Arc<Self>) -> Result<Map, PlacementError> {
+        let mut map = self.world()?;
+        map.playback = Some(Playback {
+            replay: Arc::clone(self),
+            cursor: 0,
+            diverged_at: None,
+        });
+        Ok(map)
+    }
+
+    // Avance jusqu'au tick demandé ; revenir en arrière rejoue la partie depuis le début
+    pub fn seek(self: &Arc<Self>, map: &mut Map, tick: u64) {
+        let tick = tick.min(self.length);
+        if tick < map.tick {
+            // Les sorties du journal restent branchées sur la nouvelle partie
+            let events = std::mem::take(&mut map.events);
+            // La station a été placée une première fois au chargement, et le
+            // monde se régénère à l'identique depuis la graine
+            *map = self.start().expect("replay station was placed on load");
+            map.events = events;
+        }
+        while map.tick < tick && !map.mission_over() {
+            map.update_robot();
+        }
+    }
+}
+
+impl Map {
+    // Ajoute une action à l'enregistrement en cours
+    pub fn record(&mut self, action: Action) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.actions.push((self.tick, action));
+        }
+    }
+
+    // Termine l'enregistrement en cours
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        let mut recording = self.recording.take()?;
+        recording.length = self.tick;
+        Some(recording)
+    }
+
+    // Une partie qui a divergé de son enregistrement s'arrête là
+    pub fn playback_finished(&self) -> bool {
+        self.playback.as_ref().is_some_and(|playback| {
+            self.tick >= playback.replay.length || playback.diverged_at.is_some()
+        })
+    }
+
+    pub fn replay_diverged(&self) -> Option<u64> {
+        self.playback.as_ref()?.diverged_at
+    }
+
+    // Prochaine action enregistrée si elle a eu lieu au tick courant, sans la consommer
+    fn peek_action(&self) -> Option<Action> {
+        let playback = self.playback.as_ref()?;
+        let &(tick, action) = playback.replay.actions.get(playback.cursor)?;
+        (tick == self.tick).then_some(action)
+    }
+
+    fn consume_action(&mut self) {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.cursor += 1;
+        }
+    }
+
+    // Rejoue les modifications faites par l'utilisateur entre deux ticks
+    pub fn replay_edits(&mut self) {
+        while let Some(Action::Paint(x, y, brush)) = self.peek_action() {
+            self.consume_action();
+            self.paint(x, y, brush);
+        }
+    }
+
+    // Déplacement choisi par l'explorateur pendant la partie enregistrée parmi
+    // `choices` déplacements possibles ; aucun n'est enregistré quand il n'y en a
+    // pas. Un choix manquant ou impossible interrompt la lecture
+    pub fn replayed_move(&mut self, choices: usize) -> Option<usize> {
+        let recorded = match self.peek_action() {
+            Some(Action::ExplorerMove(index)) => Some(index),
+            _ => None,
+        };
+        match recorded {
+            Some(index) if index < choices => {
+                self.consume_action();
+                Some(index)
+            }
+            None if choices == 0 => None,
+            _ => {
+                let tick = self.tick;
+                if let Some(playback) = self.playback.as_mut() {
+                    playback.diverged_at.get_or_insert(tick);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::Brush;
+    use crate::testing;
+
+    fn mission(seed: u32) -> Map {
+        let mut map = testing::mission(seed, 32, StationStrategy::MaxReachableArea);
+        map.recording = Some(Replay::new(&map));
+        map
+    }
+
+    fn play(replay: Replay) -> Map {
+        let replay = Arc::new(replay);
+        let mut map = replay.start().unwrap();
+        while map.running() {
+            map.update_robot();
+        }
+        map
+    }
+
+    #[test]
+    fn save_and_load_keep_the_replay() {
+        let mut map = mission(3);
+        map.rules.max_ticks = Some(400);
+        map.rules.victory.explored_fraction = Some(0.5);
+        for _ in 0..200 {
+            map.update_robot();
+        }
+        map.paint(0, 0, Brush::Minerals);
+        let replay = map.take_recording().unwrap();
+
+        let path = std::env::temp_dir().join(format!("cosmobots-{}.replay", std::process::id()));
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!((loaded.width, loaded.height), (replay.width, replay.height));
+        assert_eq!(loaded.topology, replay.topology);
+        assert_eq!(loaded.station, replay.station);
+        assert_eq!(loaded.thresholds, replay.thresholds);
+        assert_eq!(loaded.rules, replay.rules);
+        assert_eq!(loaded.actions, replay.actions);
+        assert_eq!(loaded.length, replay.length);
+    }
+
+    #[test]
+    fn load_reports_the_faulty_line() {
+        let path =
+            std::env::temp_dir().join(format!("cosmobots-{}-bad.replay", std::process::id()));
+        fs::write(&path, format!("{}\nseed 1\nsize 8 x\n", HEADER)).unwrap();
+        let err = Replay::load(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(err, ReplayError::Parse(3, _)), "{}", err);
+    }
+
+    // En-tête d'une partie de 32x32 cases, modifié par `edit`, puis relu
+    fn load_header(name: &str, edit: impl FnOnce(&mut Vec<String>)) -> Result<Replay, ReplayError> {
+        let path =
+            std::env::temp_dir().join(format!("cosmobots-{}-{}.replay", std::process::id(), name));
+        mission(3).take_recording().unwrap().save(&path).unwrap();
+        let mut lines: Vec<String> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        edit(&mut lines);
+        fs::write(&path, lines.join("\n")).unwrap();
+        let loaded = Replay::load(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    // Les valeurs hors bornes sont signalées à leur ligne, comme dans un scénario
+    #[test]
+    fn load_checks_the_header_values() {
+        assert!(load_header("unchanged", |_| ()).is_ok());
+        // Ligne de l'erreur quand la ligne d'en-tête commençant comme `edit` est remplacée
+        let line = |name: &str, edit: &str| {
+            let word = edit.split_whitespace().next();
+            let loaded = load_header(name, |lines| {
+                for line in lines.iter_mut() {
+                    if line.split_whitespace().next() == word {
+                        *line = edit.to_string();
+                    }
+                }
+            });
+            match loaded {
+                Err(ReplayError::Parse(line, _)) => Some(line),
+                _ => None,
+            }
+        };
+        assert_eq!(line("width", "size 0 32"), Some(3));
+        assert_eq!(line("height", "size 32 2097152"), Some(3));
+        assert_eq!(line("station", "station 40 3"), Some(5));
+        assert_eq!(line("threshold", "thresholds 0.2 1.5 0.5"), Some(6));
+        assert_eq!(line("sensor", "explorer 1000000 0"), Some(8));
+        assert_eq!(line("storm", "extractor 1 2"), Some(9));
+        assert_eq!(line("limits", "limits 0 1000 true"), Some(11));
+        assert_eq!(line("victory", "victory true 0 -"), Some(12));
+    }
+
+    #[test]
+    fn playback_reproduces_the_recorded_mission() {
+        let mut map = mission(5);
+        for tick in 0..600 {
+            map.update_robot();
+            if tick == 300 {
+                map.paint(2, 2, Brush::Obstacle);
+            }
+        }
+        let expected = map.summary();
+        let replay = map.take_recording().unwrap();
+
+        let replayed = play(replay);
+        assert_eq!(replayed.replay_diverged(), None);
+        assert_eq!(replayed.tick, 600);
+        assert_eq!(replayed.summary(), expected);
+        assert!(replayed.is_obstacle(2, 2));
+    }
+
+    // Les déplacements des explorateurs d'une flotte sont rejoués dans l'ordre
+    #[test]
+    fn playback_reproduces_a_fleet_mission() {
+        let fleet = Fleet {
+            explorers: 3,
+            extractors: 2,
+        };
+        let mut map = testing::fleet_mission(5, 32, StationStrategy::MaxReachableArea, fleet);
+        map.recording = Some(Replay::new(&map));
+        for _ in 0..400 {
+            map.update_robot();
+        }
+        let expected = map.summary();
+        let replay = map.take_recording().unwrap();
+
+        let replayed = play(replay);
+        assert_eq!(replayed.replay_diverged(), None);
+        assert_eq!(replayed.robot_explorers.len(), 3);
+        assert_eq!(replayed.summary(), expected);
+    }
+
+    #[test]
+    fn edits_survive_ticks_without_explorer_moves() {
+        // L'explorateur, enfermé à la station, n'enregistre aucun déplacement
+        let mut map = mission(7);
+        map.rules.victory.all_collected = false;
+        map.rules.stranded = false;
+        map.recording = Some(Replay::new(&map));
+        let (x, y) = map.station().unwrap();
+        for (nx, ny) in map.neighbors(x, y) {
+            map.paint(nx, ny, Brush::Obstacle);
+        }
+        let painted: Vec<(usize, usize)> = (0..5).map(|i| (i, 30)).collect();
+        for &(px, py) in &painted {
+            map.update_robot();
+            map.paint(px, py, Brush::Minerals);
+        }
+        map.update_robot();
+        let replay = map.take_recording().unwrap();
+        assert!(!replay
+            .actions
+            .iter()
+            .any(|(_, action)| matches!(action, Action::ExplorerMove(_))));
+
+        let replayed = play(replay);
+        assert_eq!(replayed.replay_diverged(), None);
+        for (px, py) in painted {
+            assert!(replayed.has_minerals(px, py));
+        }
+    }
+
+    #[test]
+    fn impossible_move_stops_the_playback() {
+        let mut map = mission(5);
+        for _ in 0..50 {
+            map.update_robot();
+        }
+        let mut replay = map.take_recording().unwrap();
+        let (tick, action) = replay
+            .actions
+            .iter_mut()
+            .find(|(_, action)| matches!(action, Action::ExplorerMove(_)))
+            .unwrap();
+        *action = Action::ExplorerMove(usize::MAX);
+        let tick = *tick;
+
+        let replayed = play(replay);
+        assert_eq!(replayed.replay_diverged(), Some(tick));
+        assert_eq!(replayed.tick, tick);
+    }
+}
@@ -1,26 +1,132 @@
 use rand::prelude::*;
-use std::collections::{BinaryHeap, HashMap};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
 
-use crate::{heuristic, Map, Node, DIRECTIONS, MAP_SIZE};
+use crate::backlog::Deposit;
+use crate::chunk::ResourceKind;
+use crate::events::SimEvent;
+use crate::pathfinding::{find_path, move_cost, SearchTrace};
+use crate::replay::Action;
+use crate::Map;
+use crate::REPORT_UNITS;
 
+// Désigne un robot de la carte par son type et son rang dans la flotte
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RobotId {
+    Explorer(usize),
+    Extractor(usize),
+}
+
+impl fmt::Display for RobotId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RobotId::Explorer(index) => write!(f, "Explorer {}", index + 1),
+            RobotId::Extractor(index) => write!(f, "Extractor {}", index + 1),
+        }
+    }
+}
+
+// Manière dont les explorateurs choisissent leur prochaine case
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplorerStrategy {
+    // Un voisin inexploré tiré au hasard, la case inexplorée la plus proche s'il n'y en a pas
+    #[default]
+    Random,
+    // Toujours la case inexplorée la plus proche, sans tirage
+    Frontier,
+}
+
+impl fmt::Display for ExplorerStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExplorerStrategy::Random => write!(f, "random"),
+            ExplorerStrategy::Frontier => write!(f, "frontier"),
+        }
+    }
+}
+
+impl FromStr for ExplorerStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(ExplorerStrategy::Random),
+            "frontier" => Ok(ExplorerStrategy::Frontier),
+            _ => Err(format!("unknown explorer strategy '{}'", s)),
+        }
+    }
+}
+
+// Avance un robot d'une case en comptabilisant l'énergie dépensée ; un
+// obstacle qu'il ne connaissait pas encore l'arrête
+fn step(
+    x: &mut usize,
+    y: &mut usize,
+    energy_used: &mut usize,
+    (next_x, next_y): (usize, usize),
+    map: &Map,
+) {
+    if map.is_blocked(next_x, next_y) {
+        return;
+    }
+    if let Some(cost) = move_cost(map, (*x, *y), (next_x, next_y)) {
+        *energy_used += cost;
+    }
+    *x = next_x;
+    *y = next_y;
+}
+
+// Cherche un chemin et le garde comme chemin prévu du robot
+fn plan(
+    from: (usize, usize),
+    to: (usize, usize),
+    path: &mut Vec<(usize, usize)>,
+    search: &mut SearchTrace,
+    map: &Map,
+) -> Option<Vec<(usize, usize)>> {
+    let found = find_path(map, from, to, search);
+    *path = found.clone().unwrap_or_default();
+    found
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RobotExplorer {
     pub x: usize,
     pub y: usize,
     pub station_x: usize,
     pub station_y: usize,
-    pub founded_resource: bool,
-    pub resource_position: Option<(usize, usize)>,
-    pub waiting: bool,
+    // Gisements repérés depuis le dernier passage à la station
+    pub deposits: Vec<Deposit>,
+    // En route pour signaler ses gisements à la station
+    pub reporting: bool,
+    // Ticks consécutifs sans pouvoir se déplacer
+    pub stuck_ticks: u64,
+    pub energy_used: usize,
+    pub path: Vec<(usize, usize)>,
+    // Trace de débogage, refaite à chaque recherche
+    #[serde(skip)]
+    pub search: SearchTrace,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RobotExtractor {
     pub x: usize,
     pub y: usize,
     pub station_x: usize,
     pub station_y: usize,
-    pub carrying_resource: bool,
+    pub cargo: Option<ResourceKind>,
     pub target_position: Option<(usize, usize)>,
     pub waiting: bool,
+    // Ticks consécutifs sans pouvoir se déplacer
+    pub stuck_ticks: u64,
+    pub energy_used: usize,
+    pub path: Vec<(usize, usize)>,
+    // Trace de débogage, refaite à chaque recherche
+    #[serde(skip)]
+    pub search: SearchTrace,
 }
 
 impl RobotExtractor {
@@ -30,9 +136,13 @@ impl RobotExtractor {
             y: station_y,
             station_x,
             station_y,
-            carrying_resource: false,
+            cargo: None,
             target_position: None,
             waiting: false, // Initialisation
+            stuck_ticks: 0,
+            energy_used: 0,
+            path: vec![],
+            search: SearchTrace::default(),
         }
     }
 
@@ -40,65 +150,47 @@ impl RobotExtractor {
         &mut self,
         target_x: usize,
         target_y: usize,
-        obstacles: &[[bool; MAP_SIZE]; MAP_SIZE],
-        fog_of_war: &[[bool; MAP_SIZE]; MAP_SIZE],
+        map: &Map,
     ) -> Option<Vec<(usize, usize)>> {
-        let start = (self.x, self.y);
-        let goal = (target_x, target_y);
-
-        let mut open_list = BinaryHeap::new();
-        let mut came_from = HashMap::new();
-        let mut g_score = vec![vec![usize::MAX; MAP_SIZE]; MAP_SIZE];
-        let mut f_score = vec![vec![usize::MAX; MAP_SIZE]; MAP_SIZE];
-
-        g_score[start.1][start.0] = 0;
-        f_score[start.1][start.0] = heuristic(start.0, start.1, goal.0, goal.1);
+        plan(
+            (self.x, self.y),
+            (target_x, target_y),
+            &mut self.path,
+            &mut self.search,
+            map,
+        )
+    }
 
-        open_list.push(Node::new(start.0, start.1, 0, f_score[start.1][start.0]));
+    pub fn step(&mut self, next: (usize, usize), map: &Map) {
+        step(&mut self.x, &mut self.y, &mut self.energy_used, next, map);
+    }
 
-        while let Some(current) = open_list.pop() {
-            if current.x == goal.0 && current.y == goal.1 {
-                let mut path = vec![];
-                let mut current_pos = (current.x, current.y);
-                while let Some(&prev) = came_from.get(&current_pos) {
-                    path.push(current_pos);
-                    current_pos = prev;
-                }
-                path.push(start);
-                path.reverse();
-                return Some(path);
-            }
+    pub fn collect_resource(&mut self, kind: ResourceKind) {
+        self.cargo = Some(kind);
+    }
 
-            for &(dx, dy) in &DIRECTIONS {
-                let neighbor_x =
-                    (current.x as isize + dx).max(0).min(MAP_SIZE as isize - 1) as usize;
-                let neighbor_y =
-                    (current.y as isize + dy).max(0).min(MAP_SIZE as isize - 1) as usize;
-                if obstacles[neighbor_y][neighbor_x] || fog_of_war[neighbor_y][neighbor_x] {
-                    continue;
-                }
-                let tentative_g_score = g_score[current.y][current.x] + 1;
-                if tentative_g_score < g_score[neighbor_y][neighbor_x] {
-                    came_from.insert((neighbor_x, neighbor_y), (current.x, current.y));
-                    g_score[neighbor_y][neighbor_x] = tentative_g_score;
-                    f_score[neighbor_y][neighbor_x] =
-                        tentative_g_score + heuristic(neighbor_x, neighbor_y, goal.0, goal.1);
-                    open_list.push(Node::new(
-                        neighbor_x,
-                        neighbor_y,
-                        tentative_g_score,
-                        f_score[neighbor_y][neighbor_x],
-                    ));
-                }
-            }
+    // Case visée par le déplacement en cours
+    pub fn target(&self) -> Option<(usize, usize)> {
+        if self.waiting {
+            None
+        } else if self.cargo.is_some() {
+            Some((self.station_x, self.station_y))
+        } else {
+            self.target_position
         }
-
-        None
     }
 
-    pub fn collect_resource(&mut self) {
-        self.carrying_resource = true;
-        println!("Collected resource at ({}, {})", self.x, self.y);
+    // État affiché dans le HUD
+    pub fn status(&self) -> &'static str {
+        if self.waiting {
+            "waiting"
+        } else if self.cargo.is_some() {
+            "carrying"
+        } else if self.target_position.is_some() {
+            "heading to deposit"
+        } else {
+            "idle"
+        }
     }
 }
 
@@ -109,127 +201,243 @@ impl RobotExplorer {
             y: station_y,
             station_x,
             station_y,
-            founded_resource: false,
-            resource_position: None,
-            waiting: false,
+            deposits: vec![],
+            reporting: false,
+            stuck_ticks: 0,
+            energy_used: 0,
+            path: vec![],
+            search: SearchTrace::default(),
         }
     }
 
-    pub fn move_random(&mut self, rng: &mut impl Rng, map: &mut Map) {
+    pub fn step(&mut self, next: (usize, usize), map: &Map) {
+        step(&mut self.x, &mut self.y, &mut self.energy_used, next, map);
+    }
+
+    // Case visée par le déplacement en cours ; l'exploration n'en a pas
+    pub fn target(&self) -> Option<(usize, usize)> {
+        if self.reporting {
+            Some((self.station_x, self.station_y))
+        } else {
+            None
+        }
+    }
+
+    // État affiché dans le HUD
+    pub fn status(&self) -> &'static str {
+        if self.reporting {
+            "returning"
+        } else {
+            "exploring"
+        }
+    }
+
+    // Unités estimées des gisements pas encore signalés
+    pub fn units_found(&self) -> usize {
+        self.deposits.iter().map(|deposit| deposit.size).sum()
+    }
+
+    // Le retour à la station vaut le trajet quand les gisements repérés
+    // représentent assez d'unités, ou dès que l'extracteur n'a plus rien à faire
+    pub fn should_report(&self, map: &Map) -> bool {
+        !self.deposits.is_empty() && (self.units_found() >= REPORT_UNITS || map.extractor_idle())
+    }
+
+    // Un pas d'exploration selon la stratégie des explorateurs
+    pub fn explore(&mut self, map: &mut Map) {
         let mut possible_moves = vec![];
 
-        for &(dx, dy) in &DIRECTIONS {
-            let new_x = (self.x as isize + dx).max(0).min(MAP_SIZE as isize - 1) as usize;
-            let new_y = (self.y as isize + dy).max(0).min(MAP_SIZE as isize - 1) as usize;
+        // Voisins inexplorés parmi lesquels la stratégie aléatoire tire
+        if map.rules.explorer_strategy == ExplorerStrategy::Random {
+            for (new_x, new_y) in map.neighbors(self.x, self.y) {
+                // Ne pas ajouter la station comme un mouvement possible
+                if (new_x, new_y) == (self.station_x, self.station_y) {
+                    continue;
+                }
 
-            // Ne pas ajouter la station comme un mouvement possible
-            if (new_x, new_y) == (self.station_x, self.station_y) {
-                continue;
-            }
+                // Les pentes trop raides sont infranchissables
+                if move_cost(map, (self.x, self.y), (new_x, new_y)).is_none() {
+                    continue;
+                }
 
-            if !map.obstacles[new_y][new_x] && !map.explored[new_y][new_x] {
-                possible_moves.push((new_x, new_y));
+                if !map.is_known_blocked(new_x, new_y) && !map.is_explored(new_x, new_y) {
+                    possible_moves.push((new_x, new_y));
+                }
             }
         }
 
-        if possible_moves.is_empty() {
-            for &(dx, dy) in &DIRECTIONS {
-                let new_x = (self.x as isize + dx).max(0).min(MAP_SIZE as isize - 1) as usize;
-                let new_y = (self.y as isize + dy).max(0).min(MAP_SIZE as isize - 1) as usize;
+        // Sans voisin inexploré à tirer, l'explorateur rejoint la case inexplorée
+        // connue la plus proche plutôt que d'errer : sinon la zone accessible ne
+        // serait jamais entièrement connue. Le chemin est gardé jusqu'à cette
+        // case, et n'est recherché à nouveau que s'il ne mène plus nulle part
+        let planned = if possible_moves.is_empty() {
+            self.frontier_step(map).or_else(|| {
+                let target = self.nearest_unexplored(map)?;
+                let path = plan(
+                    (self.x, self.y),
+                    target,
+                    &mut self.path,
+                    &mut self.search,
+                    map,
+                )?;
+                path.get(1).copied()
+            })
+        } else {
+            self.path.clear();
+            None
+        };
 
+        if possible_moves.is_empty() && planned.is_none() {
+            for (new_x, new_y) in map.neighbors(self.x, self.y) {
                 // Ne pas ajouter la station comme un mouvement possible
                 if (new_x, new_y) == (self.station_x, self.station_y) {
                     continue;
                 }
 
-                if !map.obstacles[new_y][new_x] {
+                if !map.is_known_blocked(new_x, new_y)
+                    && move_cost(map, (self.x, self.y), (new_x, new_y)).is_some()
+                {
                     possible_moves.push((new_x, new_y));
                 }
             }
         }
 
-        if let Some(&(new_x, new_y)) = possible_moves.choose(rng) {
-            self.x = new_x;
-            self.y = new_y;
-            map.explored[new_y][new_x] = true;
+        // En lecture d'une partie enregistrée, le tirage est remplacé par le choix enregistré
+        let choice = if planned.is_some() {
+            None
+        } else if map.playback.is_some() {
+            map.replayed_move(possible_moves.len())
+        } else if possible_moves.is_empty() {
+            None
+        } else {
+            Some(map.rng.gen_range(0..possible_moves.len()))
+        };
+        if let Some(index) = choice {
+            map.record(Action::ExplorerMove(index));
+        }
 
-            for dy in -1..=1 {
-                for dx in -1..=1 {
-                    let neighbor_x =
-                        (new_x as isize + dx).max(0).min(MAP_SIZE as isize - 1) as usize;
-                    let neighbor_y =
-                        (new_y as isize + dy).max(0).min(MAP_SIZE as isize - 1) as usize;
-                    map.fog_of_war[neighbor_y][neighbor_x] = false;
+        let next = planned.or_else(|| choice.and_then(|index| possible_moves.get(index).copied()));
+        if let Some((new_x, new_y)) = next {
+            self.step((new_x, new_y), map);
+            if (self.x, self.y) != (new_x, new_y) {
+                return;
+            }
+            if planned.is_some() {
+                self.path.remove(0);
+            } else {
+                self.path.clear();
+            }
+            map.cell_mut(new_x, new_y).explored = true;
+            map.reveal(new_x, new_y, map.rules.explorer);
+
+            if let Some(kind) = map.cell(new_x, new_y).and_then(|cell| cell.resource()) {
+                // Un gisement déjà repéré ou déjà confié à la station ne compte qu'une fois
+                let cells = map.survey_deposit(new_x, new_y, kind);
+                let known = map.deposit_known(&cells)
+                    || self
+                        .deposits
+                        .iter()
+                        .any(|deposit| cells.contains(&(deposit.x, deposit.y)));
+                let deposit = map.deposit_at(new_x, new_y, kind).filter(|_| !known);
+                if let Some(deposit) = deposit {
+                    self.deposits.push(deposit);
+                    map.emit(SimEvent::ResourceFound {
+                        kind,
+                        x: new_x,
+                        y: new_y,
+                    });
                 }
             }
+        }
+    }
 
-            if map.energy[new_y][new_x] {
-                self.founded_resource = true;
-                self.resource_position = Some((new_x, new_y));
-                println!("Founded energy at ({}, {}).", new_x, new_y);
-            } else if map.minerals[new_y][new_x] {
-                self.founded_resource = true;
-                self.resource_position = Some((new_x, new_y));
-                println!("Founded minerals at ({}, {}).", new_x, new_y);
+    // Prochain pas du chemin gardé vers une case inexplorée, tant que cette
+    // case reste à explorer et que le pas reste franchissable
+    fn frontier_step(&mut self, map: &Map) -> Option<(usize, usize)> {
+        let valid = match self.path[..] {
+            [start, next, .., target] | [start, next @ target] => {
+                start == (self.x, self.y)
+                    && target != (self.station_x, self.station_y)
+                    && !map.is_explored(target.0, target.1)
+                    && !map.is_known_blocked(next.0, next.1)
+                    && move_cost(map, start, next).is_some()
             }
+            _ => false,
+        };
+        if !valid {
+            self.path.clear();
+            return None;
         }
+        Some(self.path[1])
     }
 
-    pub fn return_to_station(
-        &mut self,
-        obstacles: &[[bool; MAP_SIZE]; MAP_SIZE],
-        fog_of_war: &[[bool; MAP_SIZE]; MAP_SIZE],
-    ) -> Option<Vec<(usize, usize)>> {
+    // Case connue et jamais parcourue la plus proche, atteinte en ne passant que
+    // par des cases connues et franchissables
+    fn nearest_unexplored(&self, map: &Map) -> Option<(usize, usize)> {
         let start = (self.x, self.y);
-        let goal = (self.station_x, self.station_y);
-
-        let mut open_list = BinaryHeap::new();
-        let mut came_from = HashMap::new();
-        let mut g_score = vec![vec![usize::MAX; MAP_SIZE]; MAP_SIZE];
-        let mut f_score = vec![vec![usize::MAX; MAP_SIZE]; MAP_SIZE];
-
-        g_score[start.1][start.0] = 0;
-        f_score[start.1][start.0] = heuristic(start.0, start.1, goal.0, goal.1);
-
-        open_list.push(Node::new(start.0, start.1, 0, f_score[start.1][start.0]));
-
-        while let Some(current) = open_list.pop() {
-            if current.x == goal.0 && current.y == goal.1 {
-                let mut path = vec![];
-                let mut current_pos = (current.x, current.y);
-                while let Some(&prev) = came_from.get(&current_pos) {
-                    path.push(current_pos);
-                    current_pos = prev;
-                }
-                path.push(start);
-                path.reverse();
-                return Some(path);
-            }
+        let mut reached = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
 
-            for &(dx, dy) in &DIRECTIONS {
-                let neighbor_x =
-                    (current.x as isize + dx).max(0).min(MAP_SIZE as isize - 1) as usize;
-                let neighbor_y =
-                    (current.y as isize + dy).max(0).min(MAP_SIZE as isize - 1) as usize;
-                if obstacles[neighbor_y][neighbor_x] || fog_of_war[neighbor_y][neighbor_x] {
+        while let Some((x, y)) = queue.pop_front() {
+            for next in map.neighbors(x, y) {
+                if reached.contains(&next)
+                    || map.is_fog(next.0, next.1)
+                    || map.is_known_blocked(next.0, next.1)
+                    || move_cost(map, (x, y), next).is_none()
+                {
                     continue;
                 }
-                let tentative_g_score = g_score[current.y][current.x] + 1;
-                if tentative_g_score < g_score[neighbor_y][neighbor_x] {
-                    came_from.insert((neighbor_x, neighbor_y), (current.x, current.y));
-                    g_score[neighbor_y][neighbor_x] = tentative_g_score;
-                    f_score[neighbor_y][neighbor_x] =
-                        tentative_g_score + heuristic(neighbor_x, neighbor_y, goal.0, goal.1);
-                    open_list.push(Node::new(
-                        neighbor_x,
-                        neighbor_y,
-                        tentative_g_score,
-                        f_score[neighbor_y][neighbor_x],
-                    ));
+                if !map.is_explored(next.0, next.1) && next != (self.station_x, self.station_y) {
+                    return Some(next);
                 }
+                reached.insert(next);
+                queue.push_back(next);
             }
         }
-
         None
     }
+
+    pub fn return_to_station(&mut self, map: &Map) -> Option<Vec<(usize, usize)>> {
+        let station = (self.station_x, self.station_y);
+        plan(
+            (self.x, self.y),
+            station,
+            &mut self.path,
+            &mut self.search,
+            map,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::station::StationStrategy;
+    use crate::testing;
+
+    // Le chemin vers la case inexplorée visée est suivi pas à pas, sans
+    // nouvelle recherche, tant que l'explorateur ne rentre pas à la station
+    #[test]
+    fn frontier_path_is_kept_until_reached() {
+        let mut map = testing::mission(7, 32, StationStrategy::ResourceCentroid);
+        map.rules.explorer_strategy = ExplorerStrategy::Frontier;
+        let station = map.station().unwrap();
+        let mut followed = 0;
+        while !map.mission_over() && map.tick < 2000 {
+            let before = map.robot_explorers[0].path.clone();
+            let searched = map.robot_explorers[0].search.closed.clone();
+            map.update_robot();
+            let explorer = &map.robot_explorers[0];
+            if before.len() > 2
+                && before.last() != Some(&station)
+                && explorer.path.last() != Some(&station)
+                && (explorer.x, explorer.y) == before[1]
+            {
+                assert_eq!(explorer.path, before[1..], "tick {}", map.tick);
+                assert_eq!(explorer.search.closed, searched, "tick {}", map.tick);
+                followed += 1;
+            }
+        }
+        assert!(followed > 100, "{} steps followed", followed);
+    }
 }
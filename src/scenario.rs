@@ -0,0 +1,514 @@
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::chunk::Thresholds;
+use crate::map::check_map_size;
+use crate::robot::ExplorerStrategy;
+use crate::station::StationStrategy;
+use crate::topology::Topology;
+use crate::Map;
+use crate::{
+    ENERGY_THRESHOLD, EXPLORERS, EXTRACTORS, MAP_SIZE, MAX_MAP_SIZE, MAX_ROBOTS, MAX_SENSOR_RANGE,
+    MINERALS_THRESHOLD, OBSTACLE_THRESHOLD, SENSOR_RANGE, STALL_TICKS, STORM_SENSOR_RANGE,
+};
+
+// Conditions de victoire ; la première atteinte termine la mission
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Victory {
+    // Tous les gisements accessibles depuis la station collectés et l'explorateur rentré
+    pub all_collected: bool,
+    // Unités livrées à la station
+    pub deliveries: Option<usize>,
+    // Part de la carte débarrassée du brouillard
+    pub explored_fraction: Option<f64>,
+}
+
+impl Default for Victory {
+    fn default() -> Self {
+        Victory {
+            all_collected: true,
+            deliveries: None,
+            explored_fraction: None,
+        }
+    }
+}
+
+// Caractéristiques communes aux robots d'un même type
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RobotStats {
+    pub sensor_range: usize,
+    // Portée des capteurs dans une tempête de poussière
+    pub storm_sensor_range: usize,
+}
+
+impl Default for RobotStats {
+    fn default() -> Self {
+        RobotStats {
+            sensor_range: SENSOR_RANGE,
+            storm_sensor_range: STORM_SENSOR_RANGE,
+        }
+    }
+}
+
+// Robots de chaque type déployés à la station
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fleet {
+    pub explorers: usize,
+    pub extractors: usize,
+}
+
+impl Default for Fleet {
+    fn default() -> Self {
+        Fleet {
+            explorers: EXPLORERS,
+            extractors: EXTRACTORS,
+        }
+    }
+}
+
+// Règles de la mission qui ne dépendent pas du terrain
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+    pub fleet: Fleet,
+    pub explorer: RobotStats,
+    pub extractor: RobotStats,
+    pub explorer_strategy: ExplorerStrategy,
+    pub max_ticks: Option<u64>,
+    // Ticks sans progrès avant d'abandonner la mission, 0 pour ne jamais abandonner
+    pub stall_ticks: u64,
+    // Abandon quand plus aucun robot ne peut se déplacer
+    pub stranded: bool,
+    pub victory: Victory,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            fleet: Fleet::default(),
+            explorer: RobotStats::default(),
+            extractor: RobotStats::default(),
+            explorer_strategy: ExplorerStrategy::default(),
+            max_ticks: None,
+            stall_ticks: STALL_TICKS,
+            stranded: true,
+            victory: Victory::default(),
+        }
+    }
+}
+
+// Fichier de scénario TOML ; toutes les sections et toutes les clés sont facultatives
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    pub map: MapSection,
+    pub station: StationSection,
+    pub fleet: Fleet,
+    pub robots: RobotsSection,
+    pub victory: Victory,
+    pub limits: LimitsSection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MapSection {
+    pub seed: Option<u32>,
+    pub width: usize,
+    pub height: usize,
+    #[serde(deserialize_with = "parse_string")]
+    pub topology: Topology,
+    pub obstacle_threshold: f64,
+    pub energy_threshold: f64,
+    pub minerals_threshold: f64,
+}
+
+impl Default for MapSection {
+    fn default() -> Self {
+        MapSection {
+            seed: None,
+            width: MAP_SIZE,
+            height: MAP_SIZE,
+            topology: Topology::Square8,
+            obstacle_threshold: OBSTACLE_THRESHOLD,
+            energy_threshold: ENERGY_THRESHOLD,
+            minerals_threshold: MINERALS_THRESHOLD,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StationSection {
+    #[serde(deserialize_with = "parse_string")]
+    pub placement: StationStrategy,
+}
+
+impl Default for StationSection {
+    fn default() -> Self {
+        StationSection {
+            placement: StationStrategy::ResourceCentroid,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RobotsSection {
+    #[serde(deserialize_with = "parse_string")]
+    pub explorer_strategy: ExplorerStrategy,
+    pub explorer: RobotStats,
+    pub extractor: RobotStats,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsSection {
+    pub max_ticks: Option<u64>,
+    pub stall_ticks: u64,
+    pub stranded: bool,
+}
+
+impl Default for LimitsSection {
+    fn default() -> Self {
+        LimitsSection {
+            max_ticks: None,
+            stall_ticks: STALL_TICKS,
+            stranded: true,
+        }
+    }
+}
+
+// Valeurs écrites sous forme de texte, comme en ligne de commande
+fn parse_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map_err(de::Error::custom)
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    // Syntaxe TOML, clé inconnue ou valeur du mauvais type
+    Parse(toml::de::Error),
+    // Valeur hors des bornes admises, avec le chemin de sa clé
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScenarioError::Io(err) => write!(f, "cannot read scenario: {}", err),
+            ScenarioError::Parse(err) => write!(f, "invalid scenario: {}", err),
+            ScenarioError::Invalid(key, message) => {
+                write!(f, "invalid scenario: {}: {}", key, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(err: io::Error) -> Self {
+        ScenarioError::Io(err)
+    }
+}
+
+fn check(valid: bool, key: &'static str, message: impl Into<String>) -> Result<(), ScenarioError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(ScenarioError::Invalid(key, message.into()))
+    }
+}
+
+// Seuil de génération admis : le bruit varie entre -1 et 1
+pub fn check_threshold(threshold: f64) -> Result<(), String> {
+    if (-1.0..=1.0).contains(&threshold) {
+        Ok(())
+    } else {
+        Err(format!("{} is not between -1 and 1", threshold))
+    }
+}
+
+// Nombre de robots d'un même type admis dans la flotte
+pub fn check_fleet_size(count: usize) -> Result<(), String> {
+    if (1..=MAX_ROBOTS).contains(&count) {
+        Ok(())
+    } else {
+        Err(format!("{} is not between 1 and {}", count, MAX_ROBOTS))
+    }
+}
+
+// Portée des capteurs admise : chaque observation parcourt toute la zone couverte
+pub fn check_sensor_range(range: usize) -> Result<(), String> {
+    if range <= MAX_SENSOR_RANGE {
+        Ok(())
+    } else {
+        Err(format!("{} is more than {}", range, MAX_SENSOR_RANGE))
+    }
+}
+
+// Conditions de victoire admises, avec la clé de la valeur fautive
+pub fn check_victory(victory: Victory) -> Result<(), (&'static str, String)> {
+    if victory.deliveries == Some(0) {
+        return Err(("victory.deliveries", "must be at least 1".to_string()));
+    }
+    if let Some(fraction) = victory.explored_fraction {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err((
+                "victory.explored_fraction",
+                format!("{} is not between 0 (excluded) and 1", fraction),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn check_max_ticks(max_ticks: Option<u64>) -> Result<(), String> {
+    if max_ticks == Some(0) {
+        Err("must be at least 1".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, ScenarioError> {
+        Scenario::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Scenario, ScenarioError> {
+        let scenario: Scenario = toml::from_str(text).map_err(ScenarioError::Parse)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> Result<(), ScenarioError> {
+        let map = &self.map;
+        check_map_size(map.width, MAX_MAP_SIZE)
+            .map_err(|message| ScenarioError::Invalid("map.width", message))?;
+        check_map_size(map.height, MAX_MAP_SIZE)
+            .map_err(|message| ScenarioError::Invalid("map.height", message))?;
+        for (key, threshold) in [
+            ("map.obstacle_threshold", map.obstacle_threshold),
+            ("map.energy_threshold", map.energy_threshold),
+            ("map.minerals_threshold", map.minerals_threshold),
+        ] {
+            check_threshold(threshold).map_err(|message| ScenarioError::Invalid(key, message))?;
+        }
+
+        if let StationStrategy::Fixed(x, y) = self.station.placement {
+            check(
+                x < map.width && y < map.height,
+                "station.placement",
+                format!(
+                    "({}, {}) is outside the {}x{} map",
+                    x, y, map.width, map.height
+                ),
+            )?;
+        }
+
+        for (key, count) in [
+            ("fleet.explorers", self.fleet.explorers),
+            ("fleet.extractors", self.fleet.extractors),
+        ] {
+            check_fleet_size(count).map_err(|message| ScenarioError::Invalid(key, message))?;
+        }
+        for (keys, stats) in [
+            (
+                (
+                    "robots.explorer.sensor_range",
+                    "robots.explorer.storm_sensor_range",
+                ),
+                self.robots.explorer,
+            ),
+            (
+                (
+                    "robots.extractor.sensor_range",
+                    "robots.extractor.storm_sensor_range",
+                ),
+                self.robots.extractor,
+            ),
+        ] {
+            check_sensor_range(stats.sensor_range)
+                .map_err(|message| ScenarioError::Invalid(keys.0, message))?;
+            check(
+                stats.storm_sensor_range <= stats.sensor_range,
+                keys.1,
+                "must not exceed sensor_range",
+            )?;
+        }
+
+        check_victory(self.victory)
+            .map_err(|(key, message)| ScenarioError::Invalid(key, message))?;
+        check_max_ticks(self.limits.max_ticks)
+            .map_err(|message| ScenarioError::Invalid("limits.max_ticks", message))
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            obstacle: self.map.obstacle_threshold,
+            energy: self.map.energy_threshold,
+            minerals: self.map.minerals_threshold,
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        Rules {
+            fleet: self.fleet,
+            explorer: self.robots.explorer,
+            extractor: self.robots.extractor,
+            explorer_strategy: self.robots.explorer_strategy,
+            max_ticks: self.limits.max_ticks,
+            stall_ticks: self.limits.stall_ticks,
+            stranded: self.limits.stranded,
+            victory: self.victory,
+        }
+    }
+
+    // Monde du scénario avant le placement de la station ; `seed` remplace
+    // la graine du fichier, qui est sinon tirée au hasard si elle manque
+    pub fn world(&self, seed: Option<u32>) -> Map {
+        let seed = seed.or(self.map.seed).unwrap_or_else(rand::random);
+        let mut map = Map::new(seed, self.map.width, self.map.height, self.map.topology);
+        map.generator.thresholds = self.thresholds();
+        map.rules = self.rules();
+        map.generate_initial_area();
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robot::RobotId;
+    use crate::MIN_MAP_SIZE;
+
+    fn invalid_key(text: &str) -> Option<&'static str> {
+        match Scenario::parse(text) {
+            Err(ScenarioError::Invalid(key, _)) => Some(key),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn example_scenario_is_valid() {
+        let text = include_str!("../scenarios/example.toml");
+        let scenario = Scenario::parse(text).unwrap();
+        assert_eq!(scenario.map.seed, Some(42));
+        let rules = scenario.rules();
+        assert_eq!(rules.max_ticks, Some(20000));
+        assert_eq!(
+            rules.fleet,
+            Fleet {
+                explorers: 2,
+                extractors: 3
+            }
+        );
+        assert_eq!(rules.explorer.sensor_range, 2);
+        assert_eq!(rules.extractor.sensor_range, 1);
+    }
+
+    #[test]
+    fn out_of_range_values_name_their_key() {
+        assert_eq!(
+            invalid_key("[victory]\nexplored_fraction = 1.5"),
+            Some("victory.explored_fraction")
+        );
+        assert_eq!(
+            invalid_key("[map]\nenergy_threshold = -1.2"),
+            Some("map.energy_threshold")
+        );
+        assert_eq!(
+            invalid_key("[map]\nwidth = 8\n[station]\nplacement = \"8,2\""),
+            Some("station.placement")
+        );
+        assert_eq!(
+            invalid_key("[robots.extractor]\nsensor_range = 1\nstorm_sensor_range = 2"),
+            Some("robots.extractor.storm_sensor_range")
+        );
+        assert_eq!(
+            invalid_key(&format!(
+                "[robots.explorer]\nsensor_range = {}",
+                MAX_SENSOR_RANGE + 1
+            )),
+            Some("robots.explorer.sensor_range")
+        );
+        assert_eq!(
+            invalid_key(&format!(
+                "[robots.extractor]\nsensor_range = {}",
+                MAX_SENSOR_RANGE
+            )),
+            None
+        );
+        assert_eq!(
+            invalid_key("[fleet]\nexplorers = 0"),
+            Some("fleet.explorers")
+        );
+        assert_eq!(
+            invalid_key(&format!("[fleet]\nextractors = {}", MAX_ROBOTS + 1)),
+            Some("fleet.extractors")
+        );
+        assert_eq!(
+            invalid_key("[limits]\nmax_ticks = 0"),
+            Some("limits.max_ticks")
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(matches!(
+            Scenario::parse("[map]\nsize = 8"),
+            Err(ScenarioError::Parse(_))
+        ));
+        assert!(matches!(
+            Scenario::parse("[robots]\nsensor_range = 2"),
+            Err(ScenarioError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn map_size_is_checked_at_both_ends() {
+        let size = |key: &str, value: usize| invalid_key(&format!("[map]\n{} = {}", key, value));
+        assert_eq!(size("width", MIN_MAP_SIZE), None);
+        assert_eq!(size("width", MIN_MAP_SIZE - 1), Some("map.width"));
+        assert_eq!(size("height", MAX_MAP_SIZE), None);
+        assert_eq!(size("height", MAX_MAP_SIZE + 1), Some("map.height"));
+    }
+
+    // Chaque robot de la flotte est déployé à la station avec les
+    // capteurs de son type
+    #[test]
+    fn fleet_is_deployed_with_its_stats() {
+        let scenario = Scenario::parse(
+            "[map]\nseed = 3\nwidth = 32\nheight = 32\n\
+             [fleet]\nexplorers = 2\nextractors = 3\n\
+             [robots]\nexplorer_strategy = \"frontier\"\n\
+             [robots.explorer]\nsensor_range = 2",
+        )
+        .unwrap();
+        let mut map = scenario.world(None);
+        let (x, y) = map.place_station(scenario.station.placement).unwrap();
+
+        assert_eq!(map.rules.explorer_strategy, ExplorerStrategy::Frontier);
+        assert_eq!(map.robot_explorers.len(), 2);
+        assert_eq!(map.robot_extractors.len(), 3);
+        // Carrés de 5 et 3 cases de côté sur une grille à 8 voisins
+        let explorer = map.robot_stats(RobotId::Explorer(1));
+        let extractor = map.robot_stats(RobotId::Extractor(2));
+        assert_eq!(map.sensor_area(x, y, explorer).len(), 25);
+        assert_eq!(map.sensor_area(x, y, extractor).len(), 9);
+        assert_eq!(map.visible.len(), 25);
+    }
+}
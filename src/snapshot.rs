@@ -0,0 +1,435 @@
+use rand_pcg::Pcg32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+use crate::backlog::Backlog;
+use crate::chunk::{Chunk, Thresholds, CHUNK_SIZE};
+use crate::environment::Environment;
+use crate::history::History;
+use crate::map::{check_map_size, Stockpile};
+use crate::metrics::Metrics;
+use crate::mission::{MissionOutcome, Progress};
+use crate::replay::Replay;
+use crate::scenario::Rules;
+use crate::topology::Topology;
+use crate::MAX_MAP_SIZE;
+use crate::{Map, RobotExplorer, RobotExtractor};
+
+const VERSION: u32 = 1;
+
+// Tables indexées par case, écrites comme une liste de paires triée
+// (les clés JSON ne peuvent être que des chaînes)
+pub mod pairs {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    pub fn serialize<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        K: Serialize + Ord,
+        V: Serialize,
+    {
+        let mut entries: Vec<(&K, &V)> = map.iter().collect();
+        entries.sort_by_key(|&(key, _)| key);
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+    {
+        let entries = Vec::<(K, V)>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+// État complet d'une mission à la fin d'un tick. Le générateur de terrain se
+// reconstruit à partir de la graine ; les sorties du journal et la lecture
+// d'une partie enregistrée n'en font pas partie.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    version: u32,
+    seed: u32,
+    width: usize,
+    height: usize,
+    topology: Topology,
+    thresholds: Thresholds,
+    rules: Rules,
+    tick: u64,
+    outcome: Option<MissionOutcome>,
+    progress: Progress,
+    #[serde(with = "pairs")]
+    chunks: HashMap<(usize, usize), Chunk>,
+    visible: Vec<(usize, usize)>,
+    history: History,
+    stockpile: Stockpile,
+    backlog: Backlog,
+    environment: Environment,
+    rng: Pcg32,
+    metrics: Metrics,
+    robot_explorers: Vec<RobotExplorer>,
+    robot_extractors: Vec<RobotExtractor>,
+    // Enregistrement en cours, pour que le replay couvre toute la mission
+    recording: Option<Replay>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Version(u32),
+    // Fichier lisible mais incohérent : dimensions hors bornes, robot hors de la carte...
+    Invalid(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "cannot read snapshot: {}", err),
+            SnapshotError::Parse(err) => write!(f, "invalid snapshot: {}", err),
+            SnapshotError::Version(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::Invalid(message) => write!(f, "invalid snapshot: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+impl Snapshot {
+    pub fn capture(map: &Map) -> Self {
+        let mut visible: Vec<(usize, usize)> = map.visible.iter().copied().collect();
+        visible.sort_unstable();
+        Snapshot {
+            version: VERSION,
+            seed: map.seed,
+            width: map.width,
+            height: map.height,
+            topology: map.topology,
+            thresholds: map.generator.thresholds,
+            rules: map.rules,
+            tick: map.tick,
+            outcome: map.outcome,
+            progress: map.progress,
+            chunks: map.chunks.clone(),
+            visible,
+            history: map.history.clone(),
+            stockpile: map.stockpile.clone(),
+            backlog: map.backlog.clone(),
+            environment: map.environment.clone(),
+            rng: map.rng.clone(),
+            metrics: map.metrics.clone(),
+            robot_explorers: map.robot_explorers.clone(),
+            robot_extractors: map.robot_extractors.clone(),
+            recording: map.recording.clone(),
+        }
+    }
+
+    // Vérifie ce que la reprise suppose sans le contrôler : dimensions du monde,
+    // chunks complets et dans la carte, robots, station, tempêtes et cases
+    // mémorisées sur la carte, dernier progrès antérieur au tick courant
+    fn validate(&self) -> Result<(), SnapshotError> {
+        let invalid = |message: String| Err(SnapshotError::Invalid(message));
+        for (name, size) in [("width", self.width), ("height", self.height)] {
+            if let Err(message) = check_map_size(size, MAX_MAP_SIZE) {
+                return invalid(format!("{} {}", name, message));
+            }
+        }
+        if self.progress.tick > self.tick {
+            return invalid(format!(
+                "last progress at tick {} is after the current tick {}",
+                self.progress.tick, self.tick
+            ));
+        }
+        let in_bounds = |(x, y): (usize, usize)| x < self.width && y < self.height;
+        let check = |name: &str, position: (usize, usize)| {
+            if in_bounds(position) {
+                Ok(())
+            } else {
+                Err(SnapshotError::Invalid(format!(
+                    "{} {:?} is outside the {}x{} map",
+                    name, position, self.width, self.height
+                )))
+            }
+        };
+
+        for (&(chunk_x, chunk_y), chunk) in &self.chunks {
+            if !in_bounds((chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE)) {
+                return invalid(format!(
+                    "chunk ({}, {}) is outside the map",
+                    chunk_x, chunk_y
+                ));
+            }
+            if chunk.cells.len() != CHUNK_SIZE * CHUNK_SIZE {
+                return invalid(format!(
+                    "chunk ({}, {}) has {} cells instead of {}",
+                    chunk_x,
+                    chunk_y,
+                    chunk.cells.len(),
+                    CHUNK_SIZE * CHUNK_SIZE
+                ));
+            }
+        }
+
+        let fleet = self.rules.fleet;
+        if self.robot_explorers.len() != fleet.explorers
+            || self.robot_extractors.len() != fleet.extractors
+        {
+            return invalid(format!(
+                "{} explorers and {} extractors instead of the {} and {} of the fleet",
+                self.robot_explorers.len(),
+                self.robot_extractors.len(),
+                fleet.explorers,
+                fleet.extractors
+            ));
+        }
+        // Nom, position et station de chaque robot
+        let explorers = self
+            .robot_explorers
+            .iter()
+            .enumerate()
+            .map(|(index, robot)| {
+                let name = format!("explorer {}", index + 1);
+                (name, (robot.x, robot.y), (robot.station_x, robot.station_y))
+            });
+        let extractors = self
+            .robot_extractors
+            .iter()
+            .enumerate()
+            .map(|(index, robot)| {
+                let name = format!("extractor {}", index + 1);
+                (name, (robot.x, robot.y), (robot.station_x, robot.station_y))
+            });
+        let robots: Vec<_> = explorers.chain(extractors).collect();
+        let Some(&(_, _, station)) = robots.first() else {
+            return invalid("the fleet has no robot".to_string());
+        };
+        if robots.iter().any(|robot| robot.2 != station) {
+            return invalid("the robots do not share the same station".to_string());
+        }
+        let positions = robots
+            .into_iter()
+            .map(|(name, position, _)| (name, position));
+        for (name, position) in [("station".to_string(), station)]
+            .into_iter()
+            .chain(positions)
+        {
+            check(&format!("{} position", name), position)?;
+        }
+
+        // Cases lues ou modifiées par la simulation
+        for explorer in &self.robot_explorers {
+            for deposit in &explorer.deposits {
+                check("reported deposit", (deposit.x, deposit.y))?;
+            }
+        }
+        for deposit in &self.backlog.jobs {
+            check("backlog deposit", (deposit.x, deposit.y))?;
+        }
+        for regrowth in &self.environment.regrowth {
+            check("energy regrowth", (regrowth.x, regrowth.y))?;
+        }
+        for &cell in &self.visible {
+            check("visible cell", cell)?;
+        }
+        // Les tempêtes dérivent entre les cases, leur centre reste sur la carte
+        for storm in &self.environment.storms {
+            if !(0.0..self.width as f64).contains(&storm.x)
+                || !(0.0..self.height as f64).contains(&storm.y)
+            {
+                return invalid(format!(
+                    "storm ({}, {}) is outside the {}x{} map",
+                    storm.x, storm.y, self.width, self.height
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn restore(self) -> Map {
+        let mut map = Map::new(self.seed, self.width, self.height, self.topology);
+        map.generator.thresholds = self.thresholds;
+        map.rules = self.rules;
+        map.tick = self.tick;
+        map.outcome = self.outcome;
+        map.progress = self.progress;
+        map.chunks = self.chunks;
+        map.visible = self.visible.into_iter().collect();
+        map.history = self.history;
+        map.stockpile = self.stockpile;
+        map.backlog = self.backlog;
+        map.environment = self.environment;
+        map.rng = self.rng;
+        map.metrics = self.metrics;
+        map.robot_explorers = self.robot_explorers;
+        map.robot_extractors = self.robot_extractors;
+        map.recording = self.recording;
+        map
+    }
+}
+
+impl Map {
+    pub fn save_snapshot(&self, path: &Path) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, &Snapshot::capture(self))?;
+        Ok(())
+    }
+
+    // Mission reprise exactement où la sauvegarde l'a laissée
+    pub fn load_snapshot(path: &Path) -> Result<Map, SnapshotError> {
+        let text = fs::read_to_string(path)?;
+        let snapshot: Snapshot = serde_json::from_str(&text).map_err(SnapshotError::Parse)?;
+        if snapshot.version != VERSION {
+            return Err(SnapshotError::Version(snapshot.version));
+        }
+        snapshot.validate()?;
+        Ok(snapshot.restore())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::station::StationStrategy;
+    use crate::testing;
+
+    fn mission() -> Map {
+        let mut map = testing::mission(3, 32, StationStrategy::ResourceCentroid);
+        map.rules.max_ticks = Some(1500);
+        map
+    }
+
+    fn run_to_end(map: &mut Map) {
+        while !map.mission_over() {
+            map.update_robot();
+        }
+    }
+
+    #[test]
+    fn resumed_mission_matches_the_uninterrupted_one() {
+        let mut map = mission();
+
+        while map.tick < 400 {
+            map.update_robot();
+        }
+        let path =
+            std::env::temp_dir().join(format!("cosmobots-{}-resume.snapshot", std::process::id()));
+        map.save_snapshot(&path).unwrap();
+        let resumed = Map::load_snapshot(&path);
+        fs::remove_file(&path).unwrap();
+        let mut resumed = resumed.unwrap();
+
+        let mut straight = mission();
+        run_to_end(&mut resumed);
+        run_to_end(&mut straight);
+        assert_eq!(resumed.summary(), straight.summary());
+        assert_eq!(resumed.metrics.samples, straight.metrics.samples);
+    }
+
+    // Sauvegarde de la mission modifiée par `edit`, puis rechargée
+    fn load_edited(
+        name: &str,
+        edit: impl FnOnce(&mut serde_json::Value),
+    ) -> Result<Map, SnapshotError> {
+        let path = std::env::temp_dir().join(format!(
+            "cosmobots-{}-{}.snapshot",
+            std::process::id(),
+            name
+        ));
+        mission().save_snapshot(&path).unwrap();
+        let mut value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        edit(&mut value);
+        fs::write(&path, value.to_string()).unwrap();
+        let loaded = Map::load_snapshot(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn inconsistent_snapshots_are_rejected() {
+        assert!(load_edited("unchanged", |_| ()).is_ok());
+        assert!(matches!(
+            load_edited("width", |value| value["width"] = 0.into()),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("height", |value| value["height"] = 2.into()),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("explorer", |value| {
+                value["robot_explorers"][0]["x"] = 32.into();
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("station", |value| {
+                value["robot_explorers"][0]["station_y"] = 40.into();
+                value["robot_extractors"][0]["station_y"] = 40.into();
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("fleet", |value| {
+                value["robot_extractors"] = serde_json::Value::Array(vec![]);
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn progress_after_the_current_tick_is_rejected() {
+        assert!(matches!(
+            load_edited("progress", |value| value["progress"]["tick"] = 10.into()),
+            Err(SnapshotError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn positions_outside_the_map_are_rejected() {
+        assert!(matches!(
+            load_edited("regrowth", |value| {
+                value["environment"]["regrowth"] =
+                    serde_json::json!([{ "x": 40, "y": 3, "ready_at": 1 }]);
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("storm", |value| {
+                value["environment"]["storms"] = serde_json::json!([{
+                    "x": 3.0, "y": 32.5, "dx": 0.0, "dy": 0.0, "radius": 4, "remaining": 10
+                }]);
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("backlog", |value| {
+                value["backlog"]["jobs"] = serde_json::json!([{
+                    "kind": "energy", "x": 3, "y": 100, "size": 2, "distance": 5
+                }]);
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+        assert!(matches!(
+            load_edited("visible", |value| {
+                value["visible"] = serde_json::json!([[32, 0]]);
+            }),
+            Err(SnapshotError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use ggez::graphics::{self, spritebatch::SpriteBatch, Color, DrawParam, Image};
+use ggez::{Context, GameResult};
+use std::collections::HashMap;
+
+// Éléments affichables avec une texture
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SpriteKind {
+    Ground,
+    Station,
+    Obstacle,
+    Energy,
+    Minerals,
+    ScienceInterests,
+    Robot,
+}
+
+impl SpriteKind {
+    fn path(&self) -> &'static str {
+        match self {
+            SpriteKind::Ground => "/256_Grass 02 Blades.png",
+            SpriteKind::Station => "/tile_grey.png",
+            SpriteKind::Obstacle => "/rock.png",
+            SpriteKind::Energy => "/yellow.png",
+            SpriteKind::Minerals => "/blue.png",
+            SpriteKind::ScienceInterests => "/grey.png",
+            SpriteKind::Robot => "/robot.png",
+        }
+    }
+}
+
+const SPRITE_KINDS: [SpriteKind; 7] = [
+    SpriteKind::Ground,
+    SpriteKind::Station,
+    SpriteKind::Obstacle,
+    SpriteKind::Energy,
+    SpriteKind::Minerals,
+    SpriteKind::ScienceInterests,
+    SpriteKind::Robot,
+];
+
+// Textures de terrain, dessinées sous les surcouches et les robots
+pub const TERRAIN_SPRITES: [SpriteKind; 6] = [
+    SpriteKind::Ground,
+    SpriteKind::Station,
+    SpriteKind::Obstacle,
+    SpriteKind::Energy,
+    SpriteKind::Minerals,
+    SpriteKind::ScienceInterests,
+];
+
+struct Sprite {
+    batch: SpriteBatch,
+    width: f32,
+    height: f32,
+}
+
+// Atlas des textures chargées, une SpriteBatch par image ; les éléments dont
+// l'image est absente sont dessinés avec les couleurs unies
+pub struct Sprites {
+    sprites: HashMap<SpriteKind, Sprite>,
+}
+
+impl Sprites {
+    pub fn load(ctx: &mut Context) -> Self {
+        let mut sprites = HashMap::new();
+
+        for kind in SPRITE_KINDS {
+            match Image::new(ctx, kind.path()) {
+                Ok(image) => {
+                    let (width, height) = (image.width() as f32, image.height() as f32);
+                    sprites.insert(
+                        kind,
+                        Sprite {
+                            batch: SpriteBatch::new(image),
+                            width,
+                            height,
+                        },
+                    );
+                }
+                Err(err) => eprintln!("Could not load {}: {}", kind.path(), err),
+            }
+        }
+
+        Sprites { sprites }
+    }
+
+    // Ajoute une texture centrée sur `center` ; renvoie false si elle n'est pas chargée
+    pub fn add(
+        &mut self,
+        kind: SpriteKind,
+        center: [f32; 2],
+        size: f32,
+        rotation: f32,
+        color: Color,
+    ) -> bool {
+        match self.sprites.get_mut(&kind) {
+            Some(sprite) => {
+                sprite.batch.add(
+                    DrawParam::default()
+                        .dest(center)
+                        .offset([0.5, 0.5])
+                        .rotation(rotation)
+                        .scale([size / sprite.width, size / sprite.height])
+                        .color(color),
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Dessine puis vide les batches des éléments demandés
+    pub fn draw(&mut self, ctx: &mut Context, kinds: &[SpriteKind]) -> GameResult {
+        for kind in kinds {
+            if let Some(sprite) = self.sprites.get_mut(kind) {
+                graphics::draw(ctx, &sprite.batch, DrawParam::default())?;
+                sprite.batch.clear();
+            }
+        }
+        Ok(())
+    }
+}
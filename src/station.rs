@@ -0,0 +1,290 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::pathfinding::move_cost;
+use crate::Map;
+
+// Stratégies de placement de la station
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StationStrategy {
+    Random,
+    ResourceCentroid,
+    MaxReachableArea,
+    Fixed(usize, usize),
+}
+
+// Erreurs possibles lors du placement de la station
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlacementError {
+    NoValidSite,
+    OutOfBounds(usize, usize),
+    Blocked(usize, usize),
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlacementError::NoValidSite => write!(f, "no valid site for the station"),
+            PlacementError::OutOfBounds(x, y) => {
+                write!(f, "station position ({}, {}) is outside the map", x, y)
+            }
+            PlacementError::Blocked(x, y) => write!(
+                f,
+                "station position ({}, {}) is on an obstacle or a resource",
+                x, y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlacementError {}
+
+impl fmt::Display for StationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StationStrategy::Random => write!(f, "random"),
+            StationStrategy::ResourceCentroid => write!(f, "centroid"),
+            StationStrategy::MaxReachableArea => write!(f, "reachable"),
+            StationStrategy::Fixed(x, y) => write!(f, "{},{}", x, y),
+        }
+    }
+}
+
+// Lecture d'une stratégie : "random", "centroid", "reachable" ou "x,y"
+impl FromStr for StationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(StationStrategy::Random),
+            "centroid" => Ok(StationStrategy::ResourceCentroid),
+            "reachable" => Ok(StationStrategy::MaxReachableArea),
+            _ => {
+                let (x, y) = s
+                    .split_once(',')
+                    .ok_or_else(|| format!("unknown station strategy '{}'", s))?;
+                let x = x
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid station x coordinate '{}'", x))?;
+                let y = y
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid station y coordinate '{}'", y))?;
+                Ok(StationStrategy::Fixed(x, y))
+            }
+        }
+    }
+}
+
+impl StationStrategy {
+    // Choisit l'emplacement de la station selon la stratégie
+    pub fn select(&self, map: &Map, rng: &mut impl Rng) -> Result<(usize, usize), PlacementError> {
+        let regions = connected_regions(map);
+
+        match *self {
+            StationStrategy::Random => {
+                let candidates: Vec<(usize, usize)> = free_cells(map)
+                    .into_iter()
+                    .filter(|&(x, y)| regions.has_resources(x, y))
+                    .collect();
+                let candidates = if candidates.is_empty() {
+                    free_cells(map)
+                } else {
+                    candidates
+                };
+                candidates
+                    .choose(rng)
+                    .copied()
+                    .ok_or(PlacementError::NoValidSite)
+            }
+            StationStrategy::ResourceCentroid => {
+                let resources = map.resources();
+                let count = resources.len() as f64;
+                let cx = resources.iter().map(|&(x, _)| x as f64).sum::<f64>() / count;
+                let cy = resources.iter().map(|&(_, y)| y as f64).sum::<f64>() / count;
+
+                // La station doit pouvoir atteindre au moins une ressource ; sans
+                // ressource accessible, elle se place dans la plus grande zone praticable
+                free_cells(map)
+                    .into_iter()
+                    .filter(|&(x, y)| regions.has_resources(x, y))
+                    .min_by(|&a, &b| {
+                        distance_squared(a, cx, cy).total_cmp(&distance_squared(b, cx, cy))
+                    })
+                    .map_or_else(|| StationStrategy::MaxReachableArea.select(map, rng), Ok)
+            }
+            StationStrategy::MaxReachableArea => {
+                let largest = regions.largest().ok_or(PlacementError::NoValidSite)?;
+                let cells: Vec<(usize, usize)> = free_cells(map)
+                    .into_iter()
+                    .filter(|&(x, y)| regions.label.get(&(x, y)) == Some(&largest))
+                    .collect();
+                if cells.is_empty() {
+                    return Err(PlacementError::NoValidSite);
+                }
+                let count = cells.len() as f64;
+                let cx = cells.iter().map(|&(x, _)| x as f64).sum::<f64>() / count;
+                let cy = cells.iter().map(|&(_, y)| y as f64).sum::<f64>() / count;
+                cells
+                    .into_iter()
+                    .min_by(|&a, &b| {
+                        distance_squared(a, cx, cy).total_cmp(&distance_squared(b, cx, cy))
+                    })
+                    .ok_or(PlacementError::NoValidSite)
+            }
+            StationStrategy::Fixed(x, y) => {
+                if !map.in_bounds(x, y) {
+                    return Err(PlacementError::OutOfBounds(x, y));
+                }
+                if map.cell(x, y).is_none() || !is_free(map, x, y) {
+                    return Err(PlacementError::Blocked(x, y));
+                }
+                Ok((x, y))
+            }
+        }
+    }
+}
+
+// Composantes connexes des cases praticables déjà générées
+struct Regions {
+    label: HashMap<(usize, usize), usize>,
+    sizes: Vec<usize>,
+    resources: Vec<usize>,
+}
+
+impl Regions {
+    fn has_resources(&self, x: usize, y: usize) -> bool {
+        self.label
+            .get(&(x, y))
+            .is_some_and(|&id| self.resources[id] > 0)
+    }
+
+    fn largest(&self) -> Option<usize> {
+        (0..self.sizes.len()).max_by_key(|&id| self.sizes[id])
+    }
+}
+
+fn connected_regions(map: &Map) -> Regions {
+    let mut regions = Regions {
+        label: HashMap::new(),
+        sizes: vec![],
+        resources: vec![],
+    };
+
+    for (start_x, start_y) in map.generated_cells() {
+        if map.is_obstacle(start_x, start_y) || regions.label.contains_key(&(start_x, start_y)) {
+            continue;
+        }
+
+        let id = regions.sizes.len();
+        regions.sizes.push(0);
+        regions.resources.push(0);
+        regions.label.insert((start_x, start_y), id);
+
+        let mut queue = VecDeque::new();
+        queue.push_back((start_x, start_y));
+        while let Some((x, y)) = queue.pop_front() {
+            regions.sizes[id] += 1;
+            if map.has_energy(x, y) || map.has_minerals(x, y) {
+                regions.resources[id] += 1;
+            }
+
+            for (nx, ny) in map.neighbors(x, y) {
+                if map.cell(nx, ny).is_some()
+                    && !map.is_obstacle(nx, ny)
+                    && !regions.label.contains_key(&(nx, ny))
+                    && move_cost(map, (x, y), (nx, ny)).is_some()
+                {
+                    regions.label.insert((nx, ny), id);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
+    regions
+}
+
+fn is_free(map: &Map, x: usize, y: usize) -> bool {
+    !map.is_obstacle(x, y) && !map.has_energy(x, y) && !map.has_minerals(x, y)
+}
+
+fn free_cells(map: &Map) -> Vec<(usize, usize)> {
+    map.generated_cells()
+        .into_iter()
+        .filter(|&(x, y)| is_free(map, x, y))
+        .collect()
+}
+
+fn distance_squared((x, y): (usize, usize), cx: f64, cy: f64) -> f64 {
+    (x as f64 - cx).powi(2) + (y as f64 - cy).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use crate::topology::Topology;
+
+    fn world() -> Map {
+        let mut map = Map::new(4, 32, 32, Topology::Square8);
+        map.generate_initial_area();
+        map
+    }
+
+    #[test]
+    fn fixed_position_must_be_free_and_inside_the_map() {
+        let mut map = world();
+        let mut rng = Pcg32::seed_from_u64(0);
+        assert_eq!(
+            StationStrategy::Fixed(32, 3).select(&map, &mut rng),
+            Err(PlacementError::OutOfBounds(32, 3))
+        );
+
+        map.cell_mut(5, 5).obstacle = true;
+        assert_eq!(
+            StationStrategy::Fixed(5, 5).select(&map, &mut rng),
+            Err(PlacementError::Blocked(5, 5))
+        );
+        let cell = map.cell_mut(5, 5);
+        cell.obstacle = false;
+        cell.energy = false;
+        cell.minerals = false;
+        assert_eq!(
+            StationStrategy::Fixed(5, 5).select(&map, &mut rng),
+            Ok((5, 5))
+        );
+    }
+
+    #[test]
+    fn centroid_stays_next_to_reachable_resources() {
+        let map = world();
+        let mut rng = Pcg32::seed_from_u64(0);
+        let (x, y) = StationStrategy::ResourceCentroid
+            .select(&map, &mut rng)
+            .unwrap();
+        assert!(is_free(&map, x, y));
+        assert!(connected_regions(&map).has_resources(x, y));
+    }
+
+    #[test]
+    fn centroid_without_reachable_resources_uses_the_largest_area() {
+        let mut map = world();
+        for (x, y) in map.generated_cells() {
+            let cell = map.cell_mut(x, y);
+            cell.energy = false;
+            cell.minerals = false;
+        }
+        let mut rng = Pcg32::seed_from_u64(0);
+        let centroid = StationStrategy::ResourceCentroid.select(&map, &mut rng);
+        let reachable = StationStrategy::MaxReachableArea.select(&map, &mut rng);
+        assert!(centroid.is_ok());
+        assert_eq!(centroid, reachable);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::scenario::Fleet;
+use crate::station::StationStrategy;
+use crate::topology::Topology;
+use crate::Map;
+
+// Mission prête à démarrer pour les tests : terrain généré autour du
+// centre du monde et station placée selon `strategy`
+pub fn mission(seed: u32, size: usize, strategy: StationStrategy) -> Map {
+    fleet_mission(seed, size, strategy, Fleet::default())
+}
+
+// Même mission avec une autre flotte
+pub fn fleet_mission(seed: u32, size: usize, strategy: StationStrategy, fleet: Fleet) -> Map {
+    let mut map = Map::new(seed, size, size, Topology::Square8);
+    map.rules.fleet = fleet;
+    map.generate_initial_area();
+    map.place_station(strategy).unwrap();
+    map
+}
+
+// Les robots connaissent toute la carte générée, telle qu'elle est à cet instant
+pub fn observe_all(map: &mut Map) {
+    for (x, y) in map.generated_cells() {
+        map.cell_mut(x, y).observe();
+    }
+    map.terrain_changed();
+}
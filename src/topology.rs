@@ -0,0 +1,431 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+const SQUARE4_DIRECTIONS: [(isize, isize); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+const SQUARE8_DIRECTIONS: [(isize, isize); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+// Grille hexagonale en coordonnées décalées : les lignes impaires sont
+// décalées d'une demi-case vers la droite
+const HEX_EVEN_ROW_DIRECTIONS: [(isize, isize); 6] =
+    [(1, 0), (-1, 0), (0, -1), (-1, -1), (0, 1), (-1, 1)];
+const HEX_ODD_ROW_DIRECTIONS: [(isize, isize); 6] =
+    [(1, 0), (-1, 0), (1, -1), (0, -1), (1, 1), (0, 1)];
+
+// Disposition des cases de la grille
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+    Square4,
+    Square8,
+    Hex,
+    Torus,
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Topology::Square4 => "square4",
+            Topology::Square8 => "square8",
+            Topology::Hex => "hex",
+            Topology::Torus => "torus",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "square4" => Ok(Topology::Square4),
+            "square8" => Ok(Topology::Square8),
+            "hex" => Ok(Topology::Hex),
+            "torus" => Ok(Topology::Torus),
+            _ => Err(format!("unknown topology '{}'", s)),
+        }
+    }
+}
+
+impl Topology {
+    fn directions(&self, y: usize) -> &'static [(isize, isize)] {
+        match self {
+            Topology::Square4 => &SQUARE4_DIRECTIONS,
+            Topology::Square8 | Topology::Torus => &SQUARE8_DIRECTIONS,
+            Topology::Hex if y.is_multiple_of(2) => &HEX_EVEN_ROW_DIRECTIONS,
+            Topology::Hex => &HEX_ODD_ROW_DIRECTIONS,
+        }
+    }
+
+    // Cases voisines ; sur un tore les bords se rejoignent
+    pub fn neighbors(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Vec<(usize, usize)> {
+        self.directions(y)
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                if *self == Topology::Torus {
+                    let nx = (x as isize + dx).rem_euclid(width as isize) as usize;
+                    let ny = (y as isize + dy).rem_euclid(height as isize) as usize;
+                    return Some((nx, ny));
+                }
+                let nx = x.checked_add_signed(dx)?;
+                let ny = y.checked_add_signed(dy)?;
+                (nx < width && ny < height).then_some((nx, ny))
+            })
+            .filter(|&neighbor| neighbor != (x, y))
+            .collect()
+    }
+
+    // Position continue ramenée sur la carte : les bords d'un tore se
+    // rejoignent, ceux des autres topologies arrêtent le déplacement
+    pub fn wrap_position(&self, x: f64, y: f64, width: usize, height: usize) -> (f64, f64) {
+        let (width, height) = (width as f64, height as f64);
+        if *self == Topology::Torus {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x.clamp(0.0, width - 1.0), y.clamp(0.0, height - 1.0))
+        }
+    }
+
+    // Nombre minimal de déplacements entre deux cases (heuristique de l'A*)
+    pub fn distance(
+        &self,
+        (x1, y1): (usize, usize),
+        (x2, y2): (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> usize {
+        let dx = x1.abs_diff(x2);
+        let dy = y1.abs_diff(y2);
+        match self {
+            Topology::Square4 => dx + dy,
+            Topology::Square8 => dx.max(dy),
+            Topology::Torus => dx.min(width - dx).max(dy.min(height - dy)),
+            Topology::Hex => {
+                // Passage en coordonnées axiales
+                let q1 = x1 as isize - (y1 as isize - (y1 & 1) as isize) / 2;
+                let q2 = x2 as isize - (y2 as isize - (y2 & 1) as isize) / 2;
+                let dq = q1 - q2;
+                let dr = y1 as isize - y2 as isize;
+                ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+            }
+        }
+    }
+
+    // Cases à moins de `range` déplacements, utilisé par les capteurs
+    pub fn area(
+        &self,
+        center: (usize, usize),
+        range: usize,
+        width: usize,
+        height: usize,
+    ) -> Vec<(usize, usize)> {
+        let mut visited = HashSet::new();
+        visited.insert(center);
+        let mut frontier = vec![center];
+
+        for _ in 0..range {
+            let mut next = vec![];
+            for (x, y) in frontier {
+                for neighbor in self.neighbors(x, y, width, height) {
+                    if visited.insert(neighbor) {
+                        next.push(neighbor);
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        visited.into_iter().collect()
+    }
+
+    // Coordonnées utilisées pour échantillonner le bruit ; sur un tore elles sont
+    // projetées sur deux cercles pour que la génération se raccorde aux bords
+    pub fn noise_point(
+        &self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        scale: f64,
+    ) -> [f64; 4] {
+        match self {
+            Topology::Square4 | Topology::Square8 => [x as f64 * scale, y as f64 * scale, 0.0, 0.0],
+            Topology::Hex => {
+                let offset = if y.is_multiple_of(2) { 0.0 } else { 0.5 };
+                [
+                    (x as f64 + offset) * scale,
+                    y as f64 * (3.0f64.sqrt() / 2.0) * scale,
+                    0.0,
+                    0.0,
+                ]
+            }
+            Topology::Torus => {
+                let tau = std::f64::consts::TAU;
+                let radius_x = width as f64 / tau * scale;
+                let radius_y = height as f64 / tau * scale;
+                let angle_x = x as f64 / width as f64 * tau;
+                let angle_y = y as f64 / height as f64 * tau;
+                [
+                    radius_x * angle_x.cos(),
+                    radius_x * angle_x.sin(),
+                    radius_y * angle_y.cos(),
+                    radius_y * angle_y.sin(),
+                ]
+            }
+        }
+    }
+
+    // Centre à l'écran d'une case de la vue
+    pub fn cell_center(&self, view_x: usize, view_y: usize, cell_size: f32) -> [f32; 2] {
+        match self {
+            Topology::Hex => {
+                let offset = if view_y.is_multiple_of(2) {
+                    0.0
+                } else {
+                    cell_size / 2.0
+                };
+                [
+                    view_x as f32 * cell_size + cell_size / 2.0 + offset,
+                    view_y as f32 * self.row_height(cell_size) + hex_radius(cell_size),
+                ]
+            }
+            _ => [
+                view_x as f32 * cell_size + cell_size / 2.0,
+                view_y as f32 * cell_size + cell_size / 2.0,
+            ],
+        }
+    }
+
+    // Case de la vue sous un point à l'écran, inverse de cell_center
+    pub fn cell_at(
+        &self,
+        point: [f32; 2],
+        cell_size: f32,
+        columns: usize,
+        rows: usize,
+    ) -> Option<(usize, usize)> {
+        let in_view = |x: f32, y: f32| {
+            (x >= 0.0 && y >= 0.0 && (x as usize) < columns && (y as usize) < rows)
+                .then_some((x as usize, y as usize))
+        };
+        match self {
+            Topology::Hex => {
+                // Le point appartient à l'hexagone dont le centre est le plus proche
+                let row = (point[1] / self.row_height(cell_size)).floor();
+                (-1..=1)
+                    .filter_map(|dy| {
+                        let y = row + dy as f32;
+                        let offset = if (y as i64).rem_euclid(2) == 0 {
+                            0.0
+                        } else {
+                            cell_size / 2.0
+                        };
+                        let x = ((point[0] - offset) / cell_size).floor();
+                        in_view(x, y)
+                    })
+                    .min_by(|&(ax, ay), &(bx, by)| {
+                        let distance = |(x, y): (usize, usize)| {
+                            let center = self.cell_center(x, y, cell_size);
+                            (center[0] - point[0]).powi(2) + (center[1] - point[1]).powi(2)
+                        };
+                        distance((ax, ay)).total_cmp(&distance((bx, by)))
+                    })
+            }
+            _ => in_view(
+                (point[0] / cell_size).floor(),
+                (point[1] / cell_size).floor(),
+            ),
+        }
+    }
+
+    pub fn row_height(&self, cell_size: f32) -> f32 {
+        match self {
+            Topology::Hex => hex_radius(cell_size) * 1.5,
+            _ => cell_size,
+        }
+    }
+
+    // Contour d'une case à l'écran
+    pub fn cell_outline(&self, center: [f32; 2], cell_size: f32) -> Vec<[f32; 2]> {
+        match self {
+            Topology::Hex => {
+                let radius = hex_radius(cell_size);
+                (0..6)
+                    .map(|corner| {
+                        let angle = PI / 180.0 * (60.0 * corner as f32 - 30.0);
+                        [
+                            center[0] + radius * angle.cos(),
+                            center[1] + radius * angle.sin(),
+                        ]
+                    })
+                    .collect()
+            }
+            _ => {
+                let half = cell_size / 2.0;
+                vec![
+                    [center[0] - half, center[1] - half],
+                    [center[0] + half, center[1] - half],
+                    [center[0] + half, center[1] + half],
+                    [center[0] - half, center[1] + half],
+                ]
+            }
+        }
+    }
+}
+
+// Rayon d'un hexagone « pointe en haut » de largeur cell_size
+fn hex_radius(cell_size: f32) -> f32 {
+    cell_size / 3.0f32.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGIES: [Topology; 4] = [
+        Topology::Square4,
+        Topology::Square8,
+        Topology::Hex,
+        Topology::Torus,
+    ];
+
+    fn sorted(mut cells: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn neighbors_stop_at_the_edges_except_on_a_torus() {
+        assert_eq!(
+            sorted(Topology::Square4.neighbors(0, 0, 5, 4)),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(
+            sorted(Topology::Square8.neighbors(4, 3, 5, 4)),
+            vec![(3, 2), (3, 3), (4, 2)]
+        );
+        assert_eq!(
+            sorted(Topology::Torus.neighbors(0, 0, 5, 4)),
+            vec![
+                (0, 1),
+                (0, 3),
+                (1, 0),
+                (1, 1),
+                (1, 3),
+                (4, 0),
+                (4, 1),
+                (4, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_rows_are_offset_by_their_parity() {
+        assert_eq!(
+            sorted(Topology::Hex.neighbors(2, 2, 6, 6)),
+            vec![(1, 1), (1, 2), (1, 3), (2, 1), (2, 3), (3, 2)]
+        );
+        assert_eq!(
+            sorted(Topology::Hex.neighbors(2, 1, 6, 6)),
+            vec![(1, 1), (2, 0), (2, 2), (3, 0), (3, 1), (3, 2)]
+        );
+        assert_eq!(
+            sorted(Topology::Hex.neighbors(0, 0, 6, 6)),
+            vec![(0, 1), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn neighbors_are_one_move_away() {
+        let (width, height) = (7, 6);
+        for topology in TOPOLOGIES {
+            for y in 0..height {
+                for x in 0..width {
+                    for neighbor in topology.neighbors(x, y, width, height) {
+                        assert_eq!(
+                            topology.distance((x, y), neighbor, width, height),
+                            1,
+                            "{} from ({}, {}) to {:?}",
+                            topology,
+                            x,
+                            y,
+                            neighbor
+                        );
+                        assert_eq!(topology.distance(neighbor, (x, y), width, height), 1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn distances_follow_the_topology() {
+        assert_eq!(Topology::Square4.distance((0, 0), (3, 2), 10, 10), 5);
+        assert_eq!(Topology::Square8.distance((0, 0), (3, 2), 10, 10), 3);
+        // Les bords d'un tore se rejoignent
+        assert_eq!(Topology::Torus.distance((0, 0), (9, 9), 10, 10), 1);
+        assert_eq!(Topology::Torus.distance((1, 5), (8, 0), 10, 10), 5);
+        // Trois pas le long d'une diagonale hexagonale, depuis une ligne paire ou impaire
+        assert_eq!(Topology::Hex.distance((2, 2), (3, 5), 10, 10), 3);
+        assert_eq!(Topology::Hex.distance((2, 1), (4, 4), 10, 10), 3);
+        assert_eq!(Topology::Hex.distance((0, 0), (5, 0), 10, 10), 5);
+    }
+
+    #[test]
+    fn positions_wrap_only_on_a_torus() {
+        assert_eq!(
+            Topology::Torus.wrap_position(-0.5, 10.25, 10, 8),
+            (9.5, 2.25)
+        );
+        assert_eq!(Topology::Torus.wrap_position(0.0, 0.0, 10, 8), (0.0, 0.0));
+        assert_eq!(
+            Topology::Square8.wrap_position(-0.5, 10.25, 10, 8),
+            (0.0, 7.0)
+        );
+    }
+
+    #[test]
+    fn cell_at_finds_the_cell_of_its_center() {
+        let cell_size = 30.0;
+        for topology in TOPOLOGIES {
+            for y in 0..6 {
+                for x in 0..6 {
+                    let center = topology.cell_center(x, y, cell_size);
+                    assert_eq!(
+                        topology.cell_at(center, cell_size, 6, 6),
+                        Some((x, y)),
+                        "{}",
+                        topology
+                    );
+                }
+            }
+            assert_eq!(topology.cell_at([-1.0, 5.0], cell_size, 6, 6), None);
+            assert_eq!(topology.cell_at([5.0, 1000.0], cell_size, 6, 6), None);
+        }
+        // Un point juste à gauche du centre d'une ligne impaire tombe dans sa case
+        let center = Topology::Hex.cell_center(0, 1, cell_size);
+        assert_eq!(
+            Topology::Hex.cell_at([center[0] - 10.0, center[1]], cell_size, 6, 6),
+            Some((0, 1))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{self, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use ggez::graphics::Color;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::clock::Clock;
+use crate::hud;
+use crate::map::Visibility;
+use crate::replay::Replay;
+use crate::robot::RobotId;
+use crate::topology::Topology;
+use crate::Map;
+use crate::{
+    DEFAULT_COLOR, ENERGY_COLOR, MINERALS_COLOR, OBSTACLE_COLOR, REMEMBERED_SHADE,
+    ROBOT_EXPLORER_COLOR, ROBOT_EXTRACTOR_COLOR, SCIENCE_INTERESTS_COLOR, SEEK_STEP, STATION_COLOR,
+    STORM_COLOR, TUI_FRAME, TUI_SIDEBAR_WIDTH,
+};
+
+// Conversion des couleurs de la fenêtre graphique vers le terminal
+fn rgb(color: Color) -> style::Color {
+    let (r, g, b) = color.to_rgb();
+    style::Color::Rgb { r, g, b }
+}
+
+// Même assombrissement que dans la fenêtre graphique, selon l'altitude et la visibilité
+fn shaded(color: Color, elevation: f64, visibility: Visibility) -> style::Color {
+    let mut shade = 0.7 + 0.3 * elevation as f32;
+    if visibility == Visibility::Remembered {
+        shade *= REMEMBERED_SHADE;
+    }
+    rgb(Color::new(
+        color.r * shade,
+        color.g * shade,
+        color.b * shade,
+        color.a,
+    ))
+}
+
+// Interface en mode texte : la même simulation, dessinée avec des caractères
+// Unicode et des couleurs ANSI, pour suivre une mission par SSH
+struct Tui {
+    clock: Clock,
+    // Case de la carte affichée en haut à gauche
+    origin: (usize, usize),
+    selected: RobotId,
+    follow: bool,
+    // Partie enregistrée en cours de lecture
+    replay: Option<Arc<Replay>>,
+}
+
+pub fn run(map: &mut Map, replay: Option<Arc<Replay>>) -> io::Result<()> {
+    // La sortie standard est occupée par l'affichage
+    map.events.console = None;
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+
+    let mut tui = Tui {
+        clock: Clock::new(),
+        origin: (0, 0),
+        selected: RobotId::Explorer(0),
+        follow: true,
+        replay,
+    };
+    let result = tui.run(map, &mut stdout);
+
+    // Le terminal est rendu dans son état initial même en cas d'erreur
+    execute!(stdout, ResetColor, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+impl Tui {
+    fn run(&mut self, map: &mut Map, out: &mut impl Write) -> io::Result<()> {
+        let mut last_frame = Instant::now();
+
+        loop {
+            // L'attente des touches cadence l'affichage
+            if event::poll(TUI_FRAME)? {
+                while event::poll(Duration::from_secs(0))? {
+                    match event::read()? {
+                        Event::Key(key)
+                            if key.kind == KeyEventKind::Press && !self.key_down(key.code, map) =>
+                        {
+                            return Ok(());
+                        }
+                        Event::Resize(_, _) => queue!(out, Clear(ClearType::All))?,
+                        _ => (),
+                    }
+                }
+            }
+
+            let now = Instant::now();
+            self.clock.advance(now - last_frame, || {
+                let running = map.running();
+                if running {
+                    map.update_robot();
+                }
+                running
+            });
+            last_frame = now;
+
+            self.draw(map, out)?;
+        }
+    }
+
+    // Renvoie false pour quitter
+    fn key_down(&mut self, code: KeyCode, map: &mut Map) -> bool {
+        let (x, y) = self.origin;
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            KeyCode::Left | KeyCode::Char('a') => {
+                self.origin = (x.saturating_sub(1), y);
+                self.follow = false;
+            }
+            KeyCode::Right | KeyCode::Char('d') => {
+                self.origin = ((x + 1).min(map.width - 1), y);
+                self.follow = false;
+            }
+            KeyCode::Up | KeyCode::Char('w') => {
+                self.origin = (x, y.saturating_sub(1));
+                self.follow = false;
+            }
+            KeyCode::Down | KeyCode::Char('s') => {
+                self.origin = (x, (y + 1).min(map.height - 1));
+                self.follow = false;
+            }
+            KeyCode::Tab => self.selected = map.next_robot(self.selected),
+            KeyCode::Char('f') => self.follow = !self.follow,
+            KeyCode::Char(' ') => self.clock.toggle_pause(),
+            KeyCode::Char('n') | KeyCode::Char('.') => self.clock.step(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.clock.faster(),
+            KeyCode::Char('-') => self.clock.slower(),
+            KeyCode::Char('[') => self.seek(map, map.tick.saturating_sub(SEEK_STEP)),
+            KeyCode::Char(']') => self.seek(map, map.tick + SEEK_STEP),
+            KeyCode::Home => self.seek(map, 0),
+            _ => (),
+        }
+        true
+    }
+
+    fn seek(&self, map: &mut Map, tick: u64) {
+        if let Some(replay) = &self.replay {
+            replay.seek(map, tick);
+        }
+    }
+
+    // Caractère et couleur d'une case ; chaque case occupe deux colonnes du terminal
+    fn glyph(map: &Map, x: usize, y: usize) -> (&'static str, style::Color) {
+        match map.robot_at(x, y) {
+            Some(RobotId::Explorer(_)) => return ("E ", rgb(ROBOT_EXPLORER_COLOR)),
+            Some(RobotId::Extractor(_)) => return ("X ", rgb(ROBOT_EXTRACTOR_COLOR)),
+            None => (),
+        }
+        if map.station() == Some((x, y)) {
+            return ("⌂ ", rgb(STATION_COLOR));
+        }
+        let Some(observed) = map.observed(x, y) else {
+            return ("░░", style::Color::DarkGrey);
+        };
+
+        let (glyph, color) = if observed.obstacle {
+            ("▲ ", OBSTACLE_COLOR)
+        } else if observed.energy {
+            ("◆ ", ENERGY_COLOR)
+        } else if observed.minerals {
+            ("◆ ", MINERALS_COLOR)
+        } else if observed.science_interests {
+            ("• ", SCIENCE_INTERESTS_COLOR)
+        } else {
+            ("· ", DEFAULT_COLOR)
+        };
+        (
+            glyph,
+            shaded(color, map.elevation(x, y), map.visibility(x, y)),
+        )
+    }
+
+    fn draw(&mut self, map: &Map, out: &mut impl Write) -> io::Result<()> {
+        let (columns, rows) = terminal::size()?;
+        let sidebar_x = columns.saturating_sub(TUI_SIDEBAR_WIDTH);
+        // Une demi-case de marge pour le décalage des lignes impaires en hexagonal
+        let view_columns = (sidebar_x.saturating_sub(2) / 2) as usize;
+        let view_rows = rows as usize;
+
+        if self.follow {
+            if let Some((x, y)) = map.robot_position(self.selected) {
+                self.origin = (
+                    x.saturating_sub(view_columns / 2),
+                    y.saturating_sub(view_rows / 2),
+                );
+            }
+        }
+
+        for row in 0..view_rows {
+            let y = self.origin.1 + row;
+            queue!(out, MoveTo(0, row as u16), ResetColor)?;
+            if map.topology == Topology::Hex && y % 2 == 1 {
+                queue!(out, Print(" "))?;
+            }
+            for column in 0..view_columns {
+                let x = self.origin.0 + column;
+                if !map.in_bounds(x, y) {
+                    queue!(out, ResetColor, Print("  "))?;
+                    continue;
+                }
+                let (glyph, color) = Tui::glyph(map, x, y);
+                let background = if map.in_storm(x, y) {
+                    rgb(STORM_COLOR)
+                } else {
+                    style::Color::Reset
+                };
+                queue!(
+                    out,
+                    SetForegroundColor(color),
+                    SetBackgroundColor(background),
+                    Print(glyph)
+                )?;
+            }
+            queue!(out, ResetColor, Clear(ClearType::UntilNewLine))?;
+        }
+
+        // Barre latérale : les lignes du HUD de la fenêtre graphique
+        let mut lines = hud::lines(map, &self.clock);
+        lines.push(String::new());
+        if let Some(replay) = &self.replay {
+            lines.push(match map.replay_diverged() {
+                Some(tick) => format!("Replay diverged at tick {}", tick),
+                None => format!("Replay: tick {} / {}", map.tick, replay.length),
+            });
+            lines.push("[/] seek, home restart".to_string());
+        }
+        lines.push(format!(
+            "Selected: {}{}",
+            self.selected,
+            if self.follow { " (following)" } else { "" }
+        ));
+        lines.push("q quit, space pause, n step, +/- speed".to_string());
+        lines.push("arrows/wasd pan, tab select, f follow".to_string());
+
+        for (row, line) in lines.iter().take(view_rows).enumerate() {
+            let line: String = line.chars().take(TUI_SIDEBAR_WIDTH as usize).collect();
+            queue!(
+                out,
+                MoveTo(sidebar_x, row as u16),
+                Print(line),
+                Clear(ClearType::UntilNewLine)
+            )?;
+        }
+
+        out.flush()
+    }
+}